@@ -10,7 +10,7 @@ fn main() {
         match reader.read_frame() {
             Ok(frame) => {
                 let mut fh =
-                    std::fs::File::create(&format!("{}.out", idx)).unwrap();
+                    std::fs::File::create(format!("{idx}.out")).unwrap();
                 fh.write_all(&frame.data).unwrap();
                 idx += 1;
             }
@@ -18,7 +18,7 @@ fn main() {
                 break;
             }
             Err(e) => {
-                eprintln!("{}", e);
+                eprintln!("{e}");
                 std::process::exit(1);
             }
         }
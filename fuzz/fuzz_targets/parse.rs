@@ -0,0 +1,12 @@
+#![no_main]
+
+// feeds arbitrary bytes to the parser in arbitrary chunks, to make sure
+// that malformed input never causes a panic
+libfuzzer_sys::fuzz_target!(|input: (Vec<u8>, u8)| {
+    let (bytes, chunk) = input;
+    let mut parser = ttyrec::Parser::new();
+    for chunk in bytes.chunks(usize::from(chunk.max(1))) {
+        parser.add_bytes(chunk);
+        while parser.next_extended_frame().is_some() {}
+    }
+});
@@ -0,0 +1,41 @@
+#![no_main]
+
+#[derive(Debug, arbitrary::Arbitrary)]
+struct Input {
+    frames: Vec<(u32, u32, Vec<u8>)>,
+    chunks: Vec<u8>,
+}
+
+// encodes arbitrary frames and parses them back, split into arbitrary
+// chunks, to make sure that the result is identical
+libfuzzer_sys::fuzz_target!(|input: Input| {
+    let frames: Vec<_> = input
+        .frames
+        .into_iter()
+        .map(|(secs, micros, data)| ttyrec::Frame {
+            time: std::time::Duration::new(
+                u64::from(secs),
+                (micros % 1_000_000) * 1_000,
+            ),
+            data,
+        })
+        .collect();
+    let mut bytes = vec![];
+    let encoder = ttyrec::Encoder::new();
+    for frame in &frames {
+        encoder.encode(frame, &mut bytes).unwrap();
+    }
+
+    let mut parser = ttyrec::Parser::new();
+    let mut got = vec![];
+    let mut rest = &bytes[..];
+    let mut chunks = input.chunks.iter().chain(std::iter::repeat(&u8::MAX));
+    while !rest.is_empty() {
+        let chunk = usize::from(*chunks.next().unwrap()).max(1);
+        let (chunk, next) = rest.split_at(chunk.min(rest.len()));
+        rest = next;
+        parser.add_bytes(chunk);
+        got.extend(std::iter::from_fn(|| parser.next_frame()));
+    }
+    assert_eq!(got, frames);
+});
@@ -0,0 +1,361 @@
+/// Renders a recording as an animated image.
+///
+/// Frames are run through a terminal emulator, and each distinct screen
+/// state is drawn using an embedded bitmap font. Frame delays are taken
+/// from the frame timestamps, optionally with long pauses shortened (see
+/// [`set_idle_limit`](Self::set_idle_limit)) and playback sped up (see
+/// [`set_speed`](Self::set_speed)). The last frame is shown for one second
+/// before the animation loops. Screens which are shown for longer than a
+/// single frame delay can represent (about 11 minutes for GIF and about 65
+/// seconds for APNG) are written as several identical frames.
+///
+/// Add frames in stream order with [`add_frame`](Self::add_frame), and then
+/// write the animation with [`write_gif`](Self::write_gif) (with the
+/// `"gif"` feature) or `write_apng` (with the `"png"` feature). Window
+/// resizes recorded as [`ExtendedFrame`](crate::ExtendedFrame)s are applied
+/// to the emulated terminal, but the image size is always based on the
+/// size given to [`new`](Self::new).
+pub struct Animation {
+    timeline: crate::timeline::Timeline,
+    rows: u16,
+    cols: u16,
+    palette: crate::Palette,
+    scale: usize,
+}
+
+impl Animation {
+    /// Creates a new [`Animation`] for a terminal of the given size.
+    #[must_use]
+    pub fn new(rows: u16, cols: u16) -> Self {
+        Self {
+            timeline: crate::timeline::Timeline::new(rows, cols),
+            rows,
+            cols,
+            palette: crate::Palette::new(),
+            scale: 1,
+        }
+    }
+
+    /// Sets the colors used to draw the screen.
+    pub fn set_palette(&mut self, palette: crate::Palette) {
+        self.palette = palette;
+    }
+
+    /// Sets the size of each pixel of the font, in image pixels. Each
+    /// character cell is 6x10 font pixels. Defaults to 1.
+    pub fn set_scale(&mut self, scale: u8) {
+        self.scale = usize::from(scale.max(1));
+    }
+
+    /// Sets the maximum amount of time which can pass between frames.
+    /// Longer pauses in the recording are shortened to this length. Passing
+    /// [`None`] (the default) disables this.
+    pub fn set_idle_limit(&mut self, limit: Option<std::time::Duration>) {
+        self.timeline.set_idle_limit(limit);
+    }
+
+    /// Sets the playback speed relative to the recording. For instance, 2.0
+    /// plays back at double speed. Defaults to 1.0. Only affects frames
+    /// added after it is called.
+    pub fn set_speed(&mut self, speed: f64) {
+        self.timeline.set_speed(speed);
+    }
+
+    /// Adds the next frame in the stream.
+    pub fn add_frame(&mut self, frame: &crate::frame::Frame) {
+        self.timeline.add_frame(frame);
+    }
+
+    /// Writes the animation as an animated GIF.
+    ///
+    /// # Errors
+    /// * [`Error::Encode`](crate::Error::Encode): The image was too large
+    ///   for the GIF format, or there was an error writing the image.
+    #[cfg(feature = "gif")]
+    pub fn write_gif<W: std::io::Write>(
+        &self,
+        output: W,
+    ) -> crate::Result<()> {
+        let (width, height) = self.image_size();
+        let width =
+            u16::try_from(width).map_err(|_| crate::render::too_large())?;
+        let height =
+            u16::try_from(height).map_err(|_| crate::render::too_large())?;
+        let mut encoder = gif::Encoder::new(output, width, height, &[])
+            .map_err(crate::render::encode_error)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(crate::render::encode_error)?;
+
+        let mut prev: Option<crate::render::Image> = None;
+        for (image, delay) in
+            self.frames(std::time::Duration::from_millis(10))
+        {
+            for (i, delay) in split_delay(delay, 10).enumerate() {
+                // repeated frames only need to extend the delay, so they
+                // encode no changes
+                let prev = if i == 0 { prev.as_ref() } else { Some(&image) };
+                let mut frame = gif_frame(&image, prev, width, height);
+                frame.delay = delay;
+                encoder
+                    .write_frame(&frame)
+                    .map_err(crate::render::encode_error)?;
+            }
+            prev = Some(image);
+        }
+        Ok(())
+    }
+
+    /// Writes the animation as an animated PNG.
+    ///
+    /// # Errors
+    /// * [`Error::Encode`](crate::Error::Encode): The image was too large
+    ///   for the PNG format, or there was an error writing the image.
+    #[cfg(feature = "png")]
+    pub fn write_apng<W: std::io::Write>(
+        &self,
+        output: W,
+    ) -> crate::Result<()> {
+        let (width, height) = self.image_size();
+        let width =
+            u32::try_from(width).map_err(|_| crate::render::too_large())?;
+        let height =
+            u32::try_from(height).map_err(|_| crate::render::too_large())?;
+        let resolution = std::time::Duration::from_millis(1);
+        // the frame count has to be written up front, so count the frames
+        // first and then render them one at a time, rather than keeping
+        // every rendered image in memory
+        let count = u32::try_from(
+            self.timeline
+                .durations(resolution)
+                .map(|delay| split_delay(delay, 1).count())
+                .sum::<usize>(),
+        )
+        .map_err(|_| crate::render::too_large())?;
+
+        let mut encoder = png::Encoder::new(output, width, height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .set_animated(count, 0)
+            .map_err(crate::render::encode_error)?;
+        let mut writer = encoder
+            .write_header()
+            .map_err(crate::render::encode_error)?;
+        for (image, delay) in self.frames(resolution) {
+            for delay in split_delay(delay, 1) {
+                writer
+                    .set_frame_delay(delay, 1000)
+                    .map_err(crate::render::encode_error)?;
+                writer
+                    .write_image_data(&image.pixels)
+                    .map_err(crate::render::encode_error)?;
+            }
+        }
+        writer.finish().map_err(crate::render::encode_error)
+    }
+
+    fn image_size(&self) -> (usize, usize) {
+        (
+            usize::from(self.cols) * crate::font::WIDTH * self.scale,
+            usize::from(self.rows) * crate::font::HEIGHT * self.scale,
+        )
+    }
+
+    // yields each distinct image along with how long it should be shown
+    fn frames(
+        &self,
+        resolution: std::time::Duration,
+    ) -> impl Iterator<Item = (crate::render::Image, std::time::Duration)> + '_
+    {
+        self.timeline
+            .screens(resolution)
+            .map(move |(parser, _, duration)| {
+                let image = crate::render::render(
+                    parser.screen(),
+                    &self.palette,
+                    self.rows,
+                    self.cols,
+                    self.scale,
+                );
+                (image, duration)
+            })
+    }
+}
+
+// splits a delay into frame delays (in units of the given number of
+// milliseconds) which each fit in the 16 bit delay fields used by the image
+// formats
+fn split_delay(
+    delay: std::time::Duration,
+    millis: u128,
+) -> impl Iterator<Item = u16> {
+    let mut ticks = delay.as_millis() / millis;
+    let mut first = true;
+    std::iter::from_fn(move || {
+        if ticks == 0 && !first {
+            return None;
+        }
+        first = false;
+        let delay = u16::try_from(ticks).unwrap_or(u16::MAX);
+        ticks -= u128::from(delay);
+        Some(delay)
+    })
+}
+
+// encodes only the area which changed since the previous image
+#[cfg(feature = "gif")]
+fn gif_frame(
+    image: &crate::render::Image,
+    prev: Option<&crate::render::Image>,
+    width: u16,
+    height: u16,
+) -> gif::Frame<'static> {
+    let (mut left, mut top, mut right, mut bottom) =
+        (0, 0, image.width, image.height);
+    if let Some(prev) = prev {
+        let changed = |y: usize, x: usize| {
+            let i = (y * image.width + x) * 3;
+            image.pixels[i..i + 3] != prev.pixels[i..i + 3]
+        };
+        let rows: Vec<_> = (0..image.height)
+            .filter(|&y| (0..image.width).any(|x| changed(y, x)))
+            .collect();
+        let cols: Vec<_> = (0..image.width)
+            .filter(|&x| rows.iter().any(|&y| changed(y, x)))
+            .collect();
+        if let (
+            Some(&first_row),
+            Some(&last_row),
+            Some(&first_col),
+            Some(&last_col),
+        ) = (rows.first(), rows.last(), cols.first(), cols.last())
+        {
+            (left, top, right, bottom) =
+                (first_col, first_row, last_col + 1, last_row + 1);
+        } else {
+            // gif frames can't be empty
+            (left, top, right, bottom) = (0, 0, 1, 1);
+        }
+    }
+
+    let mut rgb = Vec::with_capacity((right - left) * (bottom - top) * 3);
+    for y in top..bottom {
+        let i = (y * image.width + left) * 3;
+        let j = (y * image.width + right) * 3;
+        rgb.extend_from_slice(&image.pixels[i..j]);
+    }
+    // these all fit, since they are bounded by the full image size
+    let frame_width = u16::try_from(right - left).unwrap_or(width);
+    let frame_height = u16::try_from(bottom - top).unwrap_or(height);
+
+    let mut palette: Vec<[u8; 3]> = vec![];
+    let mut indexes = Vec::with_capacity(rgb.len() / 3);
+    for pixel in rgb.chunks_exact(3) {
+        let index = palette
+            .iter()
+            .position(|color| color == pixel)
+            .unwrap_or_else(|| {
+                palette.push([pixel[0], pixel[1], pixel[2]]);
+                palette.len() - 1
+            });
+        indexes.push(u8::try_from(index).unwrap_or(u8::MAX));
+    }
+    let mut frame = if palette.len() <= 256 {
+        gif::Frame::from_palette_pixels(
+            frame_width,
+            frame_height,
+            indexes,
+            palette.concat(),
+            None,
+        )
+    } else {
+        gif::Frame::from_rgb_speed(frame_width, frame_height, &rgb, 10)
+    };
+    frame.left = u16::try_from(left).unwrap_or(0);
+    frame.top = u16::try_from(top).unwrap_or(0);
+    frame
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    fn animation() -> Animation {
+        let mut animation = Animation::new(4, 10);
+        animation.set_idle_limit(Some(std::time::Duration::from_secs(2)));
+        animation.set_speed(2.0);
+        for (millis, data) in [
+            (0, &b"\x1b[?25l$ "[..]),
+            (1000, b"l"),
+            (1001, b"s"),
+            (11000, b"\r\n\x1b[31mfoo"),
+            (12000, b""),
+        ] {
+            animation.add_frame(&crate::frame::Frame {
+                time: std::time::Duration::from_millis(millis),
+                data: data.to_vec(),
+            });
+        }
+        animation
+    }
+
+    #[cfg(feature = "gif")]
+    #[test]
+    fn test_gif() {
+        let mut gif = vec![];
+        animation().write_gif(&mut gif).unwrap();
+        assert!(gif.starts_with(b"GIF89a"));
+        // 60x40
+        assert_eq!(&gif[6..10], &[60, 0, 40, 0]);
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_apng() {
+        let mut png = vec![];
+        animation().write_apng(&mut png).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+        let actl = png.windows(4).position(|w| w == b"acTL").unwrap();
+        let count =
+            u32::from_be_bytes(png[actl + 4..actl + 8].try_into().unwrap());
+        // the frames are counted before they are rendered, so make sure
+        // the count matches the frames actually written
+        let fctls = png.windows(4).filter(|w| w == b"fcTL").count();
+        assert_eq!(usize::try_from(count).unwrap(), fctls);
+    }
+
+    #[test]
+    fn test_split_delay() {
+        let split = |secs, millis| {
+            split_delay(std::time::Duration::from_secs(secs), millis)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(split(0, 1), [0]);
+        assert_eq!(split(1, 10), [100]);
+        assert_eq!(split(65, 1), [65000]);
+        assert_eq!(split(66, 1), [65535, 465]);
+        assert_eq!(split(1000, 10), [65535, 34465]);
+        assert_eq!(split(200, 1).len(), 4);
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_apng_long_delay() {
+        let mut animation = Animation::new(2, 5);
+        for (secs, data) in [(0, &b"a"[..]), (1000, b"b")] {
+            animation.add_frame(&crate::frame::Frame {
+                time: std::time::Duration::from_secs(secs),
+                data: data.to_vec(),
+            });
+        }
+        let mut png = vec![];
+        animation.write_apng(&mut png).unwrap();
+        let actl = png.windows(4).position(|w| w == b"acTL").unwrap();
+        let count =
+            u32::from_be_bytes(png[actl + 4..actl + 8].try_into().unwrap());
+        // 1000 seconds takes 16 frames, plus one for the final screen
+        assert_eq!(count, 17);
+    }
+}
@@ -0,0 +1,99 @@
+/// Reads ttyrec frames from several [`Reader`](super::Reader) instances,
+/// interleaving them into a single stream ordered by absolute frame time.
+///
+/// Frames are compared using their real [`Frame::time`](crate::Frame::time)
+/// values rather than their offset from the start of their own file, so
+/// this is intended for merging recordings which were made concurrently
+/// using absolute timestamps.
+pub struct Merger<T: std::io::Read> {
+    readers: Vec<super::Reader<T>>,
+    heads: crate::merge::Heads,
+}
+
+impl<T: std::io::Read> Merger<T> {
+    /// Creates a new [`Merger`] from a list of [`Reader`](super::Reader)
+    /// instances. The index of each reader in this list is used as the
+    /// [`source`](crate::MergedFrame::source) of the frames read from it.
+    #[must_use]
+    pub fn new(readers: Vec<super::Reader<T>>) -> Self {
+        let heads = crate::merge::Heads::new(readers.len());
+        Self { readers, heads }
+    }
+
+    /// Returns the frame with the earliest timestamp from across all of the
+    /// input streams.
+    ///
+    /// # Errors
+    /// * [`Error::EOF`](crate::Error::EOF): All of the input streams have
+    ///   been closed.
+    /// * [`Error::Read`](crate::Error::Read): There was an error reading from
+    ///   one of the input streams.
+    pub fn read_frame(&mut self) -> crate::Result<crate::MergedFrame> {
+        while let Some(i) = self.heads.needs() {
+            match self.readers[i].read_frame() {
+                Ok(frame) => self.heads.add(i, Some(frame)),
+                Err(crate::Error::EOF) => self.heads.add(i, None),
+                Err(e) => return Err(e),
+            }
+        }
+        self.heads.next().ok_or(crate::Error::EOF)
+    }
+
+    /// How much the timestamps in the merged stream should be offset by.
+    ///
+    /// This is the timestamp of the earliest frame across all of the input
+    /// streams. Returns [`None`] if no frames have been read yet.
+    #[must_use]
+    pub fn offset(&self) -> Option<std::time::Duration> {
+        self.heads.offset()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    fn reader(
+        frames: &[(u64, &[u8])],
+    ) -> crate::blocking::Reader<std::io::Cursor<Vec<u8>>> {
+        let mut bytes = vec![];
+        for (secs, data) in frames {
+            let frame = crate::Frame {
+                time: std::time::Duration::from_secs(*secs),
+                data: data.to_vec(),
+            };
+            bytes.extend(Vec::<u8>::try_from(frame).unwrap());
+        }
+        crate::blocking::Reader::new(std::io::Cursor::new(bytes))
+    }
+
+    #[test]
+    fn test_basic() {
+        let mut merger = Merger::new(vec![
+            reader(&[(100, b"a1"), (103, b"a2")]),
+            reader(&[]),
+            reader(&[(99, b"b1"), (103, b"b2"), (104, b"b3")]),
+        ]);
+        assert_eq!(merger.offset(), None);
+        let mut merged = vec![];
+        loop {
+            match merger.read_frame() {
+                Ok(frame) => merged.push((frame.source, frame.frame.data)),
+                Err(crate::Error::EOF) => break,
+                Err(e) => panic!("{e}"),
+            }
+        }
+        assert_eq!(
+            merged,
+            vec![
+                (2, b"b1".to_vec()),
+                (0, b"a1".to_vec()),
+                (0, b"a2".to_vec()),
+                (2, b"b2".to_vec()),
+                (2, b"b3".to_vec()),
+            ]
+        );
+        assert_eq!(merger.offset(), Some(std::time::Duration::from_secs(99)));
+    }
+}
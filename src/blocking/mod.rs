@@ -1,4 +1,8 @@
+mod merger;
+pub use merger::Merger;
 mod reader;
 pub use reader::Reader;
+mod session;
+pub use session::{SessionReader, SessionWriter};
 mod writer;
-pub use writer::Writer;
+pub use writer::{Fsync, Writer};
@@ -1,8 +1,17 @@
 /// Reads ttyrec frames from a [`std::io::Read`] instance.
+///
+/// By default, reaching the end of the input stream causes
+/// [`read_frame`](Self::read_frame) to return
+/// [`Error::EOF`](crate::Error::EOF). Readers can also be put into follow
+/// mode (like `tail -f`), where they instead wait for more data to be
+/// written - see [`set_follow`](Self::set_follow) and
+/// [`follow_path`](Self::follow_path).
 pub struct Reader<T: std::io::Read> {
     input: T,
     parser: crate::Parser,
     buf: [u8; 4096],
+    follow: Option<crate::FollowOptions>,
+    watch: Option<(crate::follow::Watch, fn(std::fs::File) -> T)>,
 }
 
 impl<T: std::io::Read> Reader<T> {
@@ -12,20 +21,33 @@ impl<T: std::io::Read> Reader<T> {
             input,
             parser: crate::Parser::new(),
             buf: [0; 4096],
+            follow: None,
+            watch: None,
         }
     }
 
     /// Returns the next parsed frame from the input stream.
     ///
     /// # Errors
-    /// * [`Error::EOF`](crate::Error::EOF): The input stream has been closed.
+    /// * [`Error::EOF`](crate::Error::EOF): The input stream has been closed
+    ///   (or in follow mode, no more data was written before the timeout).
     /// * [`Error::Read`](crate::Error::Read): There was an error reading from
-    /// the input stream.
+    ///   the input stream.
+    /// * [`Error::InvalidMicros`](crate::Error::InvalidMicros): The frame
+    ///   header was invalid. The frame has been skipped, and reading can
+    ///   continue with the next frame.
+    /// * [`Error::Truncated`](crate::Error::Truncated): The followed file was
+    ///   truncated. The file has been reopened, and reading can continue
+    ///   from its start.
+    /// * [`Error::Rotated`](crate::Error::Rotated): The followed file was
+    ///   replaced. The new file has been opened, and reading can continue
+    ///   from its start.
     // these unwraps aren't reachable
     #[allow(clippy::missing_panics_doc)]
     pub fn read_frame(&mut self) -> crate::Result<crate::Frame> {
+        let mut waiting_since = None;
         loop {
-            if let Some(frame) = self.parser.next_frame() {
+            if let Some(frame) = self.parser.try_next_frame()? {
                 return Ok(frame);
             }
             let bytes = self
@@ -33,7 +55,24 @@ impl<T: std::io::Read> Reader<T> {
                 .read(&mut self.buf)
                 .map_err(|source| crate::Error::Read { source })?;
             if bytes == 0 {
-                return Err(crate::Error::EOF);
+                let Some(follow) = self.follow else {
+                    return Err(crate::Error::EOF);
+                };
+                self.check_watch()?;
+                let waiting_since = *waiting_since
+                    .get_or_insert_with(std::time::Instant::now);
+                if follow
+                    .timeout
+                    .is_some_and(|timeout| waiting_since.elapsed() >= timeout)
+                {
+                    return Err(crate::Error::EOF);
+                }
+                std::thread::sleep(follow.poll_interval);
+                continue;
+            }
+            waiting_since = None;
+            if let Some((watch, _)) = &mut self.watch {
+                watch.advance(&self.buf[..bytes]);
             }
             self.parser.add_bytes(
                 // read() returning a value means that that many bytes are
@@ -49,4 +88,99 @@ impl<T: std::io::Read> Reader<T> {
     pub fn offset(&self) -> Option<std::time::Duration> {
         self.parser.offset()
     }
+
+    /// Sets whether frames written with an extended header should be
+    /// recognized. Defaults to `false`.
+    ///
+    /// See
+    /// [`Parser::set_extended_header`](crate::Parser::set_extended_header).
+    pub fn set_extended_header(&mut self, extended: bool) {
+        self.parser.set_extended_header(extended);
+    }
+
+    /// Enables or disables follow mode.
+    ///
+    /// In follow mode, reaching the end of the input stream causes
+    /// [`read_frame`](Self::read_frame) to poll for more data according to
+    /// the given options, rather than returning
+    /// [`Error::EOF`](crate::Error::EOF) immediately. Note that this only
+    /// makes sense for input streams which can return more data after
+    /// indicating the end of the stream, such as files.
+    pub fn set_follow(&mut self, options: Option<crate::FollowOptions>) {
+        self.follow = options;
+    }
+
+    fn check_watch(&mut self) -> crate::Result<()> {
+        let Some((watch, open)) = &mut self.watch else {
+            return Ok(());
+        };
+        let metadata = match std::fs::metadata(watch.path()) {
+            Ok(metadata) => metadata,
+            // the file may have been moved away without having been
+            // replaced yet
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(())
+            }
+            Err(source) => return Err(crate::Error::Read { source }),
+        };
+        let res = watch.check(&metadata).and_then(|()| {
+            let (offset, len) = watch.tail();
+            watch.check_tail(read_tail(watch.path(), offset, len))
+        });
+        if let Err(e) = res {
+            let file = std::fs::File::open(watch.path())
+                .map_err(|source| crate::Error::Read { source })?;
+            let metadata = file
+                .metadata()
+                .map_err(|source| crate::Error::Read { source })?;
+            watch.reset(&metadata);
+            self.input = open(file);
+            self.parser = crate::Parser::new();
+            return Err(e);
+        }
+        Ok(())
+    }
+}
+
+fn read_tail(
+    path: &std::path::Path,
+    offset: u64,
+    len: usize,
+) -> std::io::Result<Vec<u8>> {
+    use std::io::{Read as _, Seek as _};
+
+    let mut file = std::fs::File::open(path)?;
+    file.seek(std::io::SeekFrom::Start(offset))?;
+    let mut tail = vec![0; len];
+    file.read_exact(&mut tail)?;
+    Ok(tail)
+}
+
+impl Reader<std::fs::File> {
+    /// Opens the file at the given path and creates a new [`Reader`] for it
+    /// in follow mode (see [`set_follow`](Self::set_follow)).
+    ///
+    /// In addition to waiting for more data, this will also detect when the
+    /// file at the given path is truncated or replaced (for instance, by log
+    /// rotation), and reopen it.
+    ///
+    /// # Errors
+    /// * [`Error::Read`](crate::Error::Read): There was an error opening the
+    ///   file.
+    pub fn follow_path(
+        path: impl AsRef<std::path::Path>,
+        options: crate::FollowOptions,
+    ) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)
+            .map_err(|source| crate::Error::Read { source })?;
+        let metadata = file
+            .metadata()
+            .map_err(|source| crate::Error::Read { source })?;
+        let mut reader = Self::new(file);
+        reader.follow = Some(options);
+        reader.watch =
+            Some((crate::follow::Watch::new(path, &metadata), |file| file));
+        Ok(reader)
+    }
 }
@@ -0,0 +1,192 @@
+/// Writes terminal output and user input to a pair of parallel ttyrec
+/// streams.
+///
+/// Output and input are each written to their own standard ttyrec stream
+/// (so the output stream can be played back by any ttyrec player), with
+/// the timestamps of both streams generated by the output stream's
+/// [`Creator`](crate::Creator). The two streams can be recombined into a
+/// single time-ordered sequence with [`SessionReader`].
+pub struct SessionWriter<O: std::io::Write, I: std::io::Write> {
+    output: super::Writer<O>,
+    input: super::Writer<I>,
+}
+
+impl<O: std::io::Write, I: std::io::Write> SessionWriter<O, I> {
+    /// Creates a new [`SessionWriter`] from a pair of [`std::io::Write`]
+    /// instances.
+    pub fn new(output: O, input: I) -> Self {
+        Self::with_creator(output, input, crate::Creator::new())
+    }
+
+    /// Creates a new [`SessionWriter`] which uses the given
+    /// [`Creator`](crate::Creator) to timestamp frames.
+    pub fn with_creator(
+        output: O,
+        input: I,
+        creator: crate::Creator,
+    ) -> Self {
+        Self {
+            output: super::Writer::with_creator(output, creator),
+            input: super::Writer::new(input),
+        }
+    }
+
+    /// Writes a new frame to the output stream, using the current time and
+    /// given data.
+    ///
+    /// # Errors
+    /// * [`crate::Error::Write`](crate::Error::Write): There was an error
+    ///   writing to the output stream.
+    pub fn output(&mut self, data: &[u8]) -> crate::Result<()> {
+        self.output_at(std::time::Instant::now(), data)
+    }
+
+    /// Writes a new frame to the output stream, using the given time and
+    /// data.
+    ///
+    /// # Errors
+    /// * [`crate::Error::Write`](crate::Error::Write): There was an error
+    ///   writing to the output stream.
+    pub fn output_at(
+        &mut self,
+        cur_time: std::time::Instant,
+        data: &[u8],
+    ) -> crate::Result<()> {
+        self.output.frame_at(cur_time, data)
+    }
+
+    /// Writes a new frame to the input stream, using the current time and
+    /// given data.
+    ///
+    /// # Errors
+    /// * [`crate::Error::Write`](crate::Error::Write): There was an error
+    ///   writing to the input stream.
+    pub fn input(&mut self, data: &[u8]) -> crate::Result<()> {
+        self.input_at(std::time::Instant::now(), data)
+    }
+
+    /// Writes a new frame to the input stream, using the given time and
+    /// data.
+    ///
+    /// # Errors
+    /// * [`crate::Error::Write`](crate::Error::Write): There was an error
+    ///   writing to the input stream.
+    pub fn input_at(
+        &mut self,
+        cur_time: std::time::Instant,
+        data: &[u8],
+    ) -> crate::Result<()> {
+        let time = self.output.time_at(cur_time);
+        self.input.write_data(time, data)
+    }
+
+    /// Flushes both the output and input streams.
+    ///
+    /// # Errors
+    /// * [`crate::Error::Write`](crate::Error::Write): There was an error
+    ///   writing to either stream.
+    pub fn flush(&mut self) -> crate::Result<()> {
+        self.output.flush()?;
+        self.input.flush()
+    }
+
+    /// Flushes both streams and returns the underlying output and input
+    /// streams.
+    ///
+    /// # Errors
+    /// * [`crate::Error::Write`](crate::Error::Write): There was an error
+    ///   writing to either stream.
+    pub fn into_inner(self) -> crate::Result<(O, I)> {
+        Ok((self.output.into_inner()?, self.input.into_inner()?))
+    }
+}
+
+/// Reads a pair of output and input ttyrec streams (as written by
+/// [`SessionWriter`]) and merges them back into a single time-ordered
+/// sequence of [`ExtendedFrame`](crate::ExtendedFrame) values.
+///
+/// Frames from the output stream are returned as
+/// [`FrameKind::Output`](crate::FrameKind::Output) and frames from the input
+/// stream as [`FrameKind::Input`](crate::FrameKind::Input).
+pub struct SessionReader<T: std::io::Read> {
+    merger: super::Merger<T>,
+}
+
+impl<T: std::io::Read> SessionReader<T> {
+    /// Creates a new [`SessionReader`] from a pair of [`std::io::Read`]
+    /// instances.
+    pub fn new(output: T, input: T) -> Self {
+        Self {
+            merger: super::Merger::new(vec![
+                super::Reader::new(output),
+                super::Reader::new(input),
+            ]),
+        }
+    }
+
+    /// Returns the next frame from either stream.
+    ///
+    /// # Errors
+    /// * [`Error::EOF`](crate::Error::EOF): Both input streams have been
+    ///   closed.
+    /// * [`Error::Read`](crate::Error::Read): There was an error reading from
+    ///   either input stream.
+    pub fn read_frame(&mut self) -> crate::Result<crate::ExtendedFrame> {
+        let crate::MergedFrame { source, frame } =
+            self.merger.read_frame()?;
+        let kind = if source == 0 {
+            crate::FrameKind::Output(frame.data)
+        } else {
+            crate::FrameKind::Input(frame.data)
+        };
+        Ok(crate::ExtendedFrame {
+            time: frame.time,
+            kind,
+        })
+    }
+
+    /// How much the timestamps in the merged stream should be offset by.
+    ///
+    /// See [`Merger::offset`](super::Merger::offset).
+    #[must_use]
+    pub fn offset(&self) -> Option<std::time::Duration> {
+        self.merger.offset()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_basic() {
+        let base_time = std::time::Instant::now();
+        let at =
+            |millis| base_time + std::time::Duration::from_millis(millis);
+
+        let mut writer = SessionWriter::new(vec![], vec![]);
+        writer.output_at(at(0), b"$ ").unwrap();
+        writer.input_at(at(500), b"ls\r").unwrap();
+        writer.output_at(at(510), b"ls\r\nfoo bar\r\n$ ").unwrap();
+        writer.input_at(at(1500), b"\x04").unwrap();
+        let (output, input) = writer.into_inner().unwrap();
+
+        let mut reader = SessionReader::new(&output[..], &input[..]);
+        let frames: Vec<_> = std::iter::from_fn(|| reader.read_frame().ok())
+            .map(|frame| (frame.time.as_millis(), frame.kind))
+            .collect();
+        assert_eq!(
+            frames,
+            vec![
+                (0, crate::FrameKind::Output(b"$ ".to_vec())),
+                (500, crate::FrameKind::Input(b"ls\r".to_vec())),
+                (
+                    510,
+                    crate::FrameKind::Output(b"ls\r\nfoo bar\r\n$ ".to_vec())
+                ),
+                (1500, crate::FrameKind::Input(b"\x04".to_vec())),
+            ]
+        );
+    }
+}
@@ -1,24 +1,103 @@
 /// Writes ttyrec frames to a [`std::io::Write`] instance.
+///
+/// By default, each frame is written to the output stream as soon as it is
+/// created. A [`Writer`] created with
+/// [`with_capacity`](Self::with_capacity) instead collects frames in an
+/// internal buffer and only writes them out once the buffer is full, or
+/// when [`flush`](Self::flush) is called. Note that buffered frames which
+/// have not yet been written out will be lost if the [`Writer`] is dropped
+/// without being flushed. If writing to the output stream fails, the
+/// frames which weren't written are kept in the buffer, to be written by
+/// the next write or flush.
 pub struct Writer<T: std::io::Write> {
     output: T,
-    creator: crate::Creator,
+    buf: crate::buffer::Buffer,
+    sync: Option<SyncPolicy<T>>,
+}
+
+struct SyncPolicy<T> {
+    interval: std::time::Duration,
+    last_sync: std::time::Instant,
+    sync: fn(&mut T) -> std::io::Result<()>,
 }
 
 impl<T: std::io::Write> Writer<T> {
     /// Creates a new [`Writer`] from a [`std::io::Write`] instance.
     pub fn new(output: T) -> Self {
+        Self::with_creator(output, crate::Creator::new())
+    }
+
+    /// Creates a new [`Writer`] which uses the given
+    /// [`Creator`](crate::Creator) to timestamp frames.
+    ///
+    /// This can be used to write absolute timestamps, via
+    /// [`Creator::new_absolute`](crate::Creator::new_absolute).
+    pub fn with_creator(output: T, creator: crate::Creator) -> Self {
         Self {
             output,
-            creator: crate::Creator::new(),
+            buf: crate::buffer::Buffer::new(creator, 0),
+            sync: None,
         }
     }
 
+    /// Creates a new [`Writer`] from a [`std::io::Write`] instance, which
+    /// buffers up to `capacity` bytes of frame data before writing to the
+    /// output stream.
+    pub fn with_capacity(capacity: usize, output: T) -> Self {
+        let mut writer = Self::new(output);
+        writer.set_capacity(capacity);
+        writer
+    }
+
+    /// Sets the number of bytes of frame data which are buffered before
+    /// being written to the output stream. Defaults to 0 (which writes each
+    /// frame as soon as it is created), or the capacity given to
+    /// [`with_capacity`](Self::with_capacity).
+    ///
+    /// This can be combined with [`with_creator`](Self::with_creator) to
+    /// buffer frames with absolute timestamps.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.buf.set_capacity(capacity);
+    }
+
+    /// Sets the maximum amount of data written in a single frame. Frames
+    /// with more data than this are split into several frames with the same
+    /// timestamp, avoiding splitting UTF-8 characters and terminal escape
+    /// sequences where possible. Defaults to [`None`], which doesn't split
+    /// frames.
+    ///
+    /// See
+    /// [`Encoder::set_max_frame_size`](crate::Encoder::set_max_frame_size)
+    /// for details.
+    pub fn set_max_frame_size(&mut self, max_frame_size: Option<usize>) {
+        self.buf.encoder.set_max_frame_size(max_frame_size);
+    }
+
+    /// Sets how timestamps are rounded to the microsecond precision of the
+    /// ttyrec format. Defaults to
+    /// [`Rounding::Truncate`](crate::Rounding::Truncate).
+    ///
+    /// See [`Encoder::set_rounding`](crate::Encoder::set_rounding).
+    pub fn set_rounding(&mut self, rounding: crate::Rounding) {
+        self.buf.encoder.set_rounding(rounding);
+    }
+
+    /// Sets whether frames with timestamps which don't fit in an ordinary
+    /// header should be written with an extended header. Defaults to
+    /// `false`.
+    ///
+    /// See
+    /// [`Encoder::set_extended_header`](crate::Encoder::set_extended_header).
+    pub fn set_extended_header(&mut self, extended: bool) {
+        self.buf.encoder.set_extended_header(extended);
+    }
+
     /// Writes a new frame to the output stream, using the current time and
     /// given data.
     ///
     /// # Errors
     /// * [`Error::Write`](crate::Error::Write): There was an error writing to
-    /// the input stream.
+    ///   the input stream.
     pub fn frame(&mut self, data: &[u8]) -> crate::Result<()> {
         self.frame_at(std::time::Instant::now(), data)
     }
@@ -28,16 +107,346 @@ impl<T: std::io::Write> Writer<T> {
     ///
     /// # Errors
     /// * [`crate::Error::Write`](crate::Error::Write): There was an error
-    /// writing to the input stream.
+    ///   writing to the input stream.
     pub fn frame_at(
         &mut self,
         cur_time: std::time::Instant,
         data: &[u8],
     ) -> crate::Result<()> {
-        let frame = self.creator.frame_at(cur_time, data);
-        let bytes: Vec<u8> = frame.try_into()?;
+        let time = self.buf.creator.time_at(cur_time);
+        self.write_data(time, data)
+    }
+
+    /// Writes a new frame to the output stream, using the given data at the
+    /// given amount of time after the previous frame.
+    ///
+    /// See [`Creator::frame_after`](crate::Creator::frame_after).
+    ///
+    /// # Errors
+    /// * [`crate::Error::Write`](crate::Error::Write): There was an error
+    ///   writing to the input stream.
+    pub fn frame_after(
+        &mut self,
+        delay: std::time::Duration,
+        data: &[u8],
+    ) -> crate::Result<()> {
+        let time = self.buf.creator.time_after(delay);
+        self.write_data(time, data)
+    }
+
+    /// Writes an existing frame to the output stream, as is.
+    ///
+    /// Note that the timestamp of the frame is not adjusted at all, so
+    /// mixing this with the other methods for writing frames is only useful
+    /// if the timestamps of the given frames are consistent with those
+    /// generated by this writer's [`Creator`](crate::Creator).
+    ///
+    /// # Errors
+    /// * [`crate::Error::Write`](crate::Error::Write): There was an error
+    ///   writing to the input stream.
+    /// * [`crate::Error::FrameTooBig`](crate::Error::FrameTooBig): The frame
+    ///   contains too much data to be represented in a ttyrec file, and no
+    ///   maximum frame size was set with
+    ///   [`set_max_frame_size`](Self::set_max_frame_size).
+    /// * [`crate::Error::FrameTooLong`](crate::Error::FrameTooLong): The
+    ///   frame timestamp is too large to be represented in a ttyrec file.
+    pub fn write_frame(&mut self, frame: &crate::Frame) -> crate::Result<()> {
+        self.write_data(frame.time, &frame.data)
+    }
+
+    /// Writes any buffered frames to the output stream, and then flushes
+    /// the output stream.
+    ///
+    /// # Errors
+    /// * [`crate::Error::Write`](crate::Error::Write): There was an error
+    ///   writing to the input stream.
+    pub fn flush(&mut self) -> crate::Result<()> {
+        self.write_buf()?;
         self.output
-            .write_all(&bytes)
+            .flush()
             .map_err(|source| crate::Error::Write { source })
     }
+
+    /// Returns a reference to the underlying output stream.
+    pub fn get_ref(&self) -> &T {
+        &self.output
+    }
+
+    /// Returns a mutable reference to the underlying output stream.
+    ///
+    /// Note that writing directly to the output stream while there are
+    /// buffered frames will corrupt the ttyrec stream.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.output
+    }
+
+    /// Flushes any buffered frames and returns the underlying output stream.
+    ///
+    /// # Errors
+    /// * [`crate::Error::Write`](crate::Error::Write): There was an error
+    ///   writing to the input stream.
+    pub fn into_inner(mut self) -> crate::Result<T> {
+        self.flush()?;
+        Ok(self.output)
+    }
+
+    /// Returns the timestamp for a frame created at the given time,
+    /// according to this writer's [`Creator`](crate::Creator).
+    pub(crate) fn time_at(
+        &mut self,
+        cur_time: std::time::Instant,
+    ) -> std::time::Duration {
+        self.buf.creator.time_at(cur_time)
+    }
+
+    pub(crate) fn write_data(
+        &mut self,
+        time: std::time::Duration,
+        data: &[u8],
+    ) -> crate::Result<()> {
+        self.buf.push(time, data)?;
+        if self.buf.is_full() {
+            self.write_buf()?;
+        }
+        self.maybe_sync()
+    }
+
+    fn write_buf(&mut self) -> crate::Result<()> {
+        self.buf.write(|data| self.output.write(data))
+    }
+
+    fn maybe_sync(&mut self) -> crate::Result<()> {
+        let Some(policy) = &self.sync else {
+            return Ok(());
+        };
+        if policy.last_sync.elapsed() < policy.interval {
+            return Ok(());
+        }
+        let sync = policy.sync;
+        self.flush()?;
+        sync(&mut self.output)
+            .map_err(|source| crate::Error::Write { source })?;
+        if let Some(policy) = &mut self.sync {
+            policy.last_sync = std::time::Instant::now();
+        }
+        Ok(())
+    }
+}
+
+impl<T: std::io::Write + Fsync> Writer<T> {
+    /// Periodically syncs the output stream to disk.
+    ///
+    /// When set, any frame written at least `interval` after the previous
+    /// sync will cause the buffered frames to be flushed and the output
+    /// stream to be synced via [`Fsync::fsync`], limiting how much of the
+    /// recording can be lost in a crash. Passing [`None`] (the default)
+    /// disables syncing.
+    pub fn set_sync_interval(
+        &mut self,
+        interval: Option<std::time::Duration>,
+    ) {
+        self.sync = interval.map(|interval| SyncPolicy {
+            interval,
+            last_sync: std::time::Instant::now(),
+            sync: T::fsync,
+        });
+    }
+}
+
+/// Output streams which can be synced to durable storage.
+///
+/// See [`Writer::set_sync_interval`].
+pub trait Fsync {
+    /// Ensures that all data written to this stream has reached durable
+    /// storage.
+    ///
+    /// # Errors
+    /// Returns any error returned by the underlying sync operation.
+    fn fsync(&mut self) -> std::io::Result<()>;
+}
+
+impl Fsync for std::fs::File {
+    fn fsync(&mut self) -> std::io::Result<()> {
+        self.sync_data()
+    }
+}
+
+impl<T: std::io::Write + Fsync> Fsync for std::io::BufWriter<T> {
+    fn fsync(&mut self) -> std::io::Result<()> {
+        std::io::Write::flush(self)?;
+        self.get_mut().fsync()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    #[derive(Default)]
+    struct Output {
+        data: Vec<u8>,
+        fail_after: Option<usize>,
+        syncs: usize,
+    }
+
+    impl std::io::Write for Output {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if let Some(fail_after) = &mut self.fail_after {
+                if *fail_after == 0 {
+                    return Err(std::io::Error::other("failed"));
+                }
+                *fail_after -= 1;
+            }
+            // only accept a few bytes at a time, to exercise short writes
+            let len = buf.len().min(5);
+            self.data.extend_from_slice(&buf[..len]);
+            Ok(len)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Fsync for Output {
+        fn fsync(&mut self) -> std::io::Result<()> {
+            self.syncs += 1;
+            Ok(())
+        }
+    }
+
+    fn frames(data: &[u8]) -> Vec<Vec<u8>> {
+        let mut parser = crate::Parser::new();
+        parser.add_bytes(data);
+        std::iter::from_fn(|| parser.next_frame())
+            .map(|frame| frame.data)
+            .collect()
+    }
+
+    #[test]
+    fn test_basic() {
+        let mut writer = Writer::new(Output::default());
+        writer.frame(b"foo").unwrap();
+        assert_eq!(frames(&writer.get_ref().data), [b"foo"]);
+
+        let mut writer = Writer::with_capacity(100, Output::default());
+        writer
+            .frame_after(std::time::Duration::ZERO, b"foo")
+            .unwrap();
+        writer
+            .frame_after(std::time::Duration::from_secs(1), b"bar")
+            .unwrap();
+        assert!(writer.get_ref().data.is_empty());
+        writer.flush().unwrap();
+        assert_eq!(frames(&writer.get_ref().data), [b"foo", b"bar"]);
+
+        // a failed write keeps whatever wasn't written yet
+        writer
+            .frame_after(std::time::Duration::from_secs(1), b"baz")
+            .unwrap();
+        writer.get_mut().fail_after = Some(2);
+        assert!(matches!(writer.flush(), Err(crate::Error::Write { .. })));
+        writer.get_mut().fail_after = None;
+        writer
+            .frame_after(std::time::Duration::from_secs(1), b"quux")
+            .unwrap();
+        let output = writer.into_inner().unwrap();
+        assert_eq!(
+            frames(&output.data),
+            [&b"foo"[..], b"bar", b"baz", b"quux"]
+        );
+    }
+
+    #[test]
+    fn test_extended_header() {
+        let frame = crate::Frame {
+            time: std::time::Duration::new(5_000_000_000, 1_500),
+            data: b"foo".to_vec(),
+        };
+        let mut writer = Writer::new(Output::default());
+        assert!(matches!(
+            writer.write_frame(&frame),
+            Err(crate::Error::FrameTooLong { .. })
+        ));
+        writer.set_extended_header(true);
+        writer.set_rounding(crate::Rounding::Up);
+        writer.write_frame(&frame).unwrap();
+        let output = writer.into_inner().unwrap();
+
+        let mut reader = crate::blocking::Reader::new(&output.data[..]);
+        reader.set_extended_header(true);
+        assert_eq!(
+            reader.read_frame().unwrap(),
+            crate::Frame {
+                time: std::time::Duration::new(5_000_000_000, 2_000),
+                data: b"foo".to_vec(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_max_frame_size() {
+        let mut writer = Writer::new(Output::default());
+        writer.set_max_frame_size(Some(4));
+        writer.frame(b"foobar").unwrap();
+        writer.frame(b"\x1b[31mfoo").unwrap();
+        writer.set_max_frame_size(None);
+        writer.frame(b"foobar").unwrap();
+        let output = writer.into_inner().unwrap();
+        assert_eq!(
+            frames(&output.data),
+            [&b"foob"[..], b"ar", b"\x1b[31", b"mfoo", b"foobar",]
+        );
+    }
+
+    #[test]
+    fn test_capacity() {
+        let start =
+            std::time::UNIX_EPOCH + std::time::Duration::from_secs(1000);
+        let mut writer = Writer::with_creator(
+            Output::default(),
+            crate::Creator::with_start_time(start),
+        );
+        writer.set_capacity(100);
+        writer
+            .frame_after(std::time::Duration::ZERO, b"foo")
+            .unwrap();
+        writer
+            .frame_after(std::time::Duration::from_secs(1), b"bar")
+            .unwrap();
+        assert!(writer.get_ref().data.is_empty());
+
+        // frames which are already buffered are written by the next write
+        // once the capacity is lowered
+        writer.set_capacity(0);
+        writer
+            .frame_after(std::time::Duration::from_secs(1), b"baz")
+            .unwrap();
+        let mut parser = crate::Parser::new();
+        parser.add_bytes(&writer.get_ref().data);
+        let times: Vec<_> = std::iter::from_fn(|| parser.next_frame())
+            .map(|frame| frame.time.as_secs())
+            .collect();
+        assert_eq!(times, [1000, 1001, 1002]);
+    }
+
+    #[test]
+    fn test_sync_interval() {
+        let mut writer = Writer::with_capacity(100, Output::default());
+        writer.set_sync_interval(Some(std::time::Duration::ZERO));
+        writer.frame(b"foo").unwrap();
+        assert_eq!(writer.get_ref().syncs, 1);
+        assert_eq!(frames(&writer.get_ref().data), [b"foo"]);
+
+        writer.set_sync_interval(Some(std::time::Duration::MAX));
+        writer.frame(b"bar").unwrap();
+        assert_eq!(writer.get_ref().syncs, 1);
+        assert_eq!(frames(&writer.get_ref().data), [b"foo"]);
+
+        writer.set_sync_interval(None);
+        writer.flush().unwrap();
+        assert_eq!(writer.get_ref().syncs, 1);
+        assert_eq!(frames(&writer.get_ref().data), [b"foo", b"bar"]);
+    }
 }
@@ -0,0 +1,380 @@
+use tokio::io::AsyncWriteExt as _;
+
+/// Fans out a live ttyrec stream to any number of viewers.
+///
+/// Frames are given to the [`Broadcaster`] using the same methods as a
+/// [`Writer`](crate::Writer), and each frame is sent to every connected
+/// viewer in the ttyrec wire format. Viewers can be attached at any time,
+/// either directly via [`add_client`](Self::add_client) or by accepting
+/// connections with [`serve_tcp`](Self::serve_tcp) or
+/// [`serve_unix`](Self::serve_unix).
+///
+/// The frames are also run through a terminal parser, so that viewers who
+/// join partway through the recording are first sent a single frame which
+/// reproduces the current state of the screen, rather than starting from a
+/// blank screen.
+///
+/// Each viewer has a bounded buffer of frames which have not yet been sent.
+/// Sending frames never waits for viewers - if a viewer falls far enough
+/// behind that its buffer fills up, it is disconnected instead.
+///
+/// [`Broadcaster`] is a cheap handle to shared state, so it can be cloned
+/// to be used from multiple tasks.
+#[derive(Clone)]
+pub struct Broadcaster {
+    inner: std::sync::Arc<std::sync::Mutex<Inner>>,
+}
+
+struct Inner {
+    creator: crate::Creator,
+    encoder: crate::Encoder,
+    screen: vt100::Parser,
+    last_time: Option<std::time::Duration>,
+    clients: Vec<tokio::sync::mpsc::Sender<std::sync::Arc<Vec<u8>>>>,
+    client_buffer: usize,
+}
+
+impl Broadcaster {
+    /// Creates a new [`Broadcaster`] for a terminal of the given size.
+    ///
+    /// The size is used to reconstruct the screen contents for viewers who
+    /// join partway through the recording.
+    #[must_use]
+    pub fn new(rows: u16, cols: u16) -> Self {
+        Self {
+            inner: std::sync::Arc::new(std::sync::Mutex::new(Inner {
+                creator: crate::Creator::new(),
+                encoder: crate::Encoder::new(),
+                screen: vt100::Parser::new(rows, cols, 0),
+                last_time: None,
+                clients: vec![],
+                client_buffer: 1024,
+            })),
+        }
+    }
+
+    /// Sets the number of frames which can be buffered for each viewer
+    /// before that viewer is disconnected. Defaults to 1024. This only
+    /// affects viewers which are added after it is called.
+    pub fn set_client_buffer(&self, frames: usize) {
+        self.lock().client_buffer = frames.max(1);
+    }
+
+    /// Changes the size of the terminal being broadcast.
+    pub fn set_size(&self, rows: u16, cols: u16) {
+        self.lock().screen.screen_mut().set_size(rows, cols);
+    }
+
+    /// Returns the number of currently connected viewers.
+    #[must_use]
+    pub fn clients(&self) -> usize {
+        let mut inner = self.lock();
+        inner.clients.retain(|client| !client.is_closed());
+        inner.clients.len()
+    }
+
+    /// Broadcasts a new frame, using the current time and given data.
+    ///
+    /// # Errors
+    /// * [`Error::FrameTooBig`](crate::Error::FrameTooBig): The frame
+    ///   contains too much data to be represented in a ttyrec file.
+    /// * [`Error::FrameTooLong`](crate::Error::FrameTooLong): The frame
+    ///   timestamp is too large to be represented in a ttyrec file.
+    pub fn frame(&self, data: &[u8]) -> crate::Result<()> {
+        self.frame_at(std::time::Instant::now(), data)
+    }
+
+    /// Broadcasts a new frame, using the given time and data.
+    ///
+    /// # Errors
+    /// * [`Error::FrameTooBig`](crate::Error::FrameTooBig): The frame
+    ///   contains too much data to be represented in a ttyrec file.
+    /// * [`Error::FrameTooLong`](crate::Error::FrameTooLong): The frame
+    ///   timestamp is too large to be represented in a ttyrec file.
+    pub fn frame_at(
+        &self,
+        cur_time: std::time::Instant,
+        data: &[u8],
+    ) -> crate::Result<()> {
+        let mut inner = self.lock();
+        let frame = inner.creator.frame_at(cur_time, data);
+        inner.send(&frame)
+    }
+
+    /// Broadcasts a new frame, using the given data at the given amount of
+    /// time after the previous frame.
+    ///
+    /// See [`Creator::frame_after`](crate::Creator::frame_after).
+    ///
+    /// # Errors
+    /// * [`Error::FrameTooBig`](crate::Error::FrameTooBig): The frame
+    ///   contains too much data to be represented in a ttyrec file.
+    /// * [`Error::FrameTooLong`](crate::Error::FrameTooLong): The frame
+    ///   timestamp is too large to be represented in a ttyrec file.
+    pub fn frame_after(
+        &self,
+        delay: std::time::Duration,
+        data: &[u8],
+    ) -> crate::Result<()> {
+        let mut inner = self.lock();
+        let frame = inner.creator.frame_after(delay, data);
+        inner.send(&frame)
+    }
+
+    /// Broadcasts an existing frame, as is.
+    ///
+    /// # Errors
+    /// * [`Error::FrameTooBig`](crate::Error::FrameTooBig): The frame
+    ///   contains too much data to be represented in a ttyrec file.
+    /// * [`Error::FrameTooLong`](crate::Error::FrameTooLong): The frame
+    ///   timestamp is too large to be represented in a ttyrec file.
+    pub fn write_frame(&self, frame: &crate::Frame) -> crate::Result<()> {
+        self.lock().send(frame)
+    }
+
+    /// Adds a new viewer which will be sent the broadcast stream.
+    ///
+    /// This spawns a task on the current tokio runtime which writes frames
+    /// to the given output stream until either the viewer is disconnected
+    /// or the [`Broadcaster`] (and all of its clones) are dropped.
+    ///
+    /// # Panics
+    /// Panics if called outside of a tokio runtime.
+    pub fn add_client<W>(
+        &self,
+        output: W,
+    ) -> tokio::task::JoinHandle<crate::Result<()>>
+    where
+        W: tokio::io::AsyncWrite + std::marker::Unpin + Send + 'static,
+    {
+        tokio::task::spawn(self.client(output))
+    }
+
+    /// Accepts connections on the given TCP listener, adding each one as a
+    /// viewer. This only returns if accepting a connection fails.
+    ///
+    /// If writing to a viewer fails, that viewer is disconnected and the
+    /// error is passed to `on_client_error`. Viewers which are still
+    /// connected when this returns are left running, but dropping the
+    /// returned future before then disconnects them.
+    ///
+    /// # Errors
+    /// * [`Error::Accept`](crate::Error::Accept): There was an error
+    ///   accepting a new connection.
+    pub async fn serve_tcp(
+        &self,
+        listener: tokio::net::TcpListener,
+        on_client_error: impl FnMut(crate::Error),
+    ) -> crate::Result<()> {
+        self.serve(|| listener.accept(), on_client_error).await
+    }
+
+    /// Accepts connections on the given unix socket listener, adding each
+    /// one as a viewer. This only returns if accepting a connection fails.
+    ///
+    /// Viewer errors are handled in the same way as by
+    /// [`serve_tcp`](Self::serve_tcp).
+    ///
+    /// # Errors
+    /// * [`Error::Accept`](crate::Error::Accept): There was an error
+    ///   accepting a new connection.
+    #[cfg(unix)]
+    pub async fn serve_unix(
+        &self,
+        listener: tokio::net::UnixListener,
+        on_client_error: impl FnMut(crate::Error),
+    ) -> crate::Result<()> {
+        self.serve(|| listener.accept(), on_client_error).await
+    }
+
+    async fn serve<S, A, F>(
+        &self,
+        mut accept: impl FnMut() -> F,
+        mut on_client_error: impl FnMut(crate::Error),
+    ) -> crate::Result<()>
+    where
+        S: tokio::io::AsyncWrite + std::marker::Unpin + Send + 'static,
+        F: std::future::Future<Output = std::io::Result<(S, A)>>,
+    {
+        let mut clients = tokio::task::JoinSet::new();
+        loop {
+            tokio::select! {
+                res = accept() => {
+                    match res {
+                        Ok((stream, _)) => {
+                            clients.spawn(self.client(stream));
+                        }
+                        Err(source) => {
+                            clients.detach_all();
+                            return Err(crate::Error::Accept { source });
+                        }
+                    }
+                }
+                Some(res) = clients.join_next() => match res {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => on_client_error(e),
+                    Err(e) => {
+                        if let Ok(panic) = e.try_into_panic() {
+                            std::panic::resume_unwind(panic);
+                        }
+                    }
+                },
+            }
+        }
+    }
+
+    fn client<W>(
+        &self,
+        mut output: W,
+    ) -> impl std::future::Future<Output = crate::Result<()>>
+    where
+        W: tokio::io::AsyncWrite + std::marker::Unpin + Send + 'static,
+    {
+        let mut receiver = self.lock().add_client();
+        async move {
+            while let Some(bytes) = receiver.recv().await {
+                output
+                    .write_all(&bytes)
+                    .await
+                    .map_err(|source| crate::Error::Write { source })?;
+            }
+            output
+                .shutdown()
+                .await
+                .map_err(|source| crate::Error::Write { source })
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        // a panic while the lock is held can at worst leave the screen
+        // state slightly out of date, which isn't worth propagating
+        self.inner
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl Inner {
+    fn send(&mut self, frame: &crate::Frame) -> crate::Result<()> {
+        let mut bytes = vec![];
+        self.encoder.encode(frame, &mut bytes)?;
+        self.screen.process(&frame.data);
+        self.last_time = Some(frame.time);
+
+        let bytes = std::sync::Arc::new(bytes);
+        self.clients
+            .retain(|client| client.try_send(bytes.clone()).is_ok());
+        Ok(())
+    }
+
+    fn add_client(
+        &mut self,
+    ) -> tokio::sync::mpsc::Receiver<std::sync::Arc<Vec<u8>>> {
+        let (sender, receiver) =
+            tokio::sync::mpsc::channel(self.client_buffer);
+        if let Some(time) = self.last_time {
+            let frame = crate::Frame {
+                time,
+                data: self.screen.screen().state_formatted(),
+            };
+            let mut bytes = vec![];
+            // the time has already been successfully encoded once, and the
+            // screen state is far smaller than the maximum frame size
+            if self.encoder.encode(&frame, &mut bytes).is_ok() {
+                // can't fail, since the channel was just created with
+                // space for at least one frame
+                let _ = sender.try_send(std::sync::Arc::new(bytes));
+            }
+        }
+        self.clients.push(sender);
+        receiver
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_basic() {
+        let broadcaster = Broadcaster::new(24, 80);
+        let (early, early_output) = tokio::io::duplex(4096);
+        broadcaster.add_client(early);
+        broadcaster
+            .frame_after(std::time::Duration::ZERO, b"\x1b[2Jfoo")
+            .unwrap();
+        broadcaster
+            .frame_after(std::time::Duration::from_secs(1), b"bar")
+            .unwrap();
+        let (late, late_output) = tokio::io::duplex(4096);
+        broadcaster.add_client(late);
+        broadcaster
+            .frame_after(std::time::Duration::from_secs(1), b"baz")
+            .unwrap();
+        assert_eq!(broadcaster.clients(), 2);
+        drop(broadcaster);
+
+        let mut reader = crate::Reader::new(early_output);
+        for (secs, data) in
+            [(0, &b"\x1b[2Jfoo"[..]), (1, b"bar"), (2, b"baz")]
+        {
+            let frame = reader.read_frame().await.unwrap();
+            assert_eq!(frame.time, std::time::Duration::from_secs(secs));
+            assert_eq!(frame.data, data);
+        }
+        assert!(matches!(reader.read_frame().await, Err(crate::Error::EOF)));
+
+        let mut reader = crate::Reader::new(late_output);
+        let frame = reader.read_frame().await.unwrap();
+        assert_eq!(frame.time, std::time::Duration::from_secs(1));
+        let mut screen = vt100::Parser::new(24, 80, 0);
+        screen.process(&frame.data);
+        assert_eq!(screen.screen().contents(), "foobar");
+        let frame = reader.read_frame().await.unwrap();
+        assert_eq!(frame.time, std::time::Duration::from_secs(2));
+        assert_eq!(frame.data, b"baz");
+        assert!(matches!(reader.read_frame().await, Err(crate::Error::EOF)));
+    }
+
+    #[tokio::test]
+    async fn test_slow_client() {
+        let broadcaster = Broadcaster::new(24, 80);
+        broadcaster.set_client_buffer(2);
+        let (client, _output) = tokio::io::duplex(4096);
+        broadcaster.add_client(client);
+        for _ in 0..3 {
+            broadcaster.frame(b"foo").unwrap();
+        }
+        assert_eq!(broadcaster.clients(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_serve() {
+        let broadcaster = Broadcaster::new(24, 80);
+        broadcaster.frame(b"foo").unwrap();
+
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let receiver = std::sync::Arc::new(tokio::sync::Mutex::new(receiver));
+        let accept = || {
+            let receiver = receiver.clone();
+            async move { receiver.lock().await.recv().await.unwrap() }
+        };
+
+        // a viewer which has already disconnected
+        let (client, _) = tokio::io::duplex(4096);
+        sender.send(Ok((client, ()))).unwrap();
+        let mut errors = vec![];
+        let res = broadcaster
+            .serve(accept, |e| {
+                errors.push(e);
+                sender
+                    .send(Err(std::io::ErrorKind::ConnectionAborted.into()))
+                    .unwrap();
+            })
+            .await;
+        assert!(matches!(res, Err(crate::Error::Accept { .. })));
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0], crate::Error::Write { .. }));
+    }
+}
@@ -0,0 +1,100 @@
+/// Encoded frames which are waiting to be written to an output stream.
+///
+/// This holds the state shared by the blocking, tokio, and futures writers,
+/// which only differ in how they write to their output streams (passed in
+/// to [`write`](Self::write) or [`poll_write`](Self::poll_write) as a
+/// callback).
+#[derive(Debug)]
+pub struct Buffer {
+    pub creator: crate::Creator,
+    pub encoder: crate::Encoder,
+    buf: Vec<u8>,
+    written: usize,
+    capacity: usize,
+}
+
+impl Buffer {
+    pub fn new(creator: crate::Creator, capacity: usize) -> Self {
+        Self {
+            creator,
+            encoder: crate::Encoder::new(),
+            buf: Vec::with_capacity(capacity),
+            written: 0,
+            capacity,
+        }
+    }
+
+    /// Sets how much data can be buffered before [`is_full`](Self::is_full)
+    /// returns true.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.buf.reserve(capacity.saturating_sub(self.buf.len()));
+        self.capacity = capacity;
+    }
+
+    /// Encodes a frame into the buffer.
+    pub fn push(
+        &mut self,
+        time: std::time::Duration,
+        data: &[u8],
+    ) -> crate::Result<()> {
+        self.encoder.encode_data(time, data, &mut self.buf)
+    }
+
+    /// Returns true if the buffer should be written out before more frames
+    /// are added.
+    pub fn is_full(&self) -> bool {
+        self.buf.len() >= self.capacity
+    }
+
+    /// Writes out the buffer using the given callback, which should behave
+    /// like [`std::io::Write::write`]. On error, whatever wasn't written is
+    /// kept, so that nothing is lost if the write is retried.
+    pub fn write(
+        &mut self,
+        mut write: impl FnMut(&[u8]) -> std::io::Result<usize>,
+    ) -> crate::Result<()> {
+        while self.written < self.buf.len() {
+            self.advance(write(&self.buf[self.written..]))?;
+        }
+        self.clear();
+        Ok(())
+    }
+
+    /// The same as [`write`](Self::write), but for callbacks which behave
+    /// like `AsyncWrite::poll_write`.
+    #[cfg(any(feature = "async", feature = "futures"))]
+    pub fn poll_write(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+        mut write: impl FnMut(
+            &mut std::task::Context<'_>,
+            &[u8],
+        ) -> std::task::Poll<std::io::Result<usize>>,
+    ) -> std::task::Poll<crate::Result<()>> {
+        while self.written < self.buf.len() {
+            let res = std::task::ready!(write(cx, &self.buf[self.written..]));
+            self.advance(res)?;
+        }
+        self.clear();
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn advance(&mut self, res: std::io::Result<usize>) -> crate::Result<()> {
+        match res {
+            Ok(0) => Err(crate::Error::Write {
+                source: std::io::ErrorKind::WriteZero.into(),
+            }),
+            Ok(n) => {
+                self.written += n;
+                Ok(())
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => Ok(()),
+            Err(source) => Err(crate::Error::Write { source }),
+        }
+    }
+
+    fn clear(&mut self) {
+        self.buf.clear();
+        self.written = 0;
+    }
+}
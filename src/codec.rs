@@ -0,0 +1,291 @@
+/// A [`tokio_util::codec`] implementation for ttyrec frames.
+///
+/// This allows ttyrec streams to be layered on top of any transport
+/// supported by [`tokio_util::codec::Framed`],
+/// [`FramedRead`](tokio_util::codec::FramedRead), or
+/// [`FramedWrite`](tokio_util::codec::FramedWrite). Frames are decoded in
+/// the same way as by [`Parser`](crate::Parser), and encoded by an
+/// [`Encoder`](crate::Encoder).
+///
+/// Errors are reported as [`std::io::Error`] values, as required by
+/// [`tokio_util::codec::Framed`]. Errors from this crate (such as
+/// [`Error::FrameTooBig`](crate::Error::FrameTooBig)) are wrapped in an
+/// [`std::io::Error`] of kind [`std::io::ErrorKind::InvalidInput`] when
+/// encoding, or [`std::io::ErrorKind::InvalidData`] when decoding (such as
+/// [`Error::InvalidMicros`](crate::Error::InvalidMicros)).
+#[derive(Debug, Clone)]
+pub struct Codec {
+    encoder: crate::encoder::Encoder,
+    max_frame_length: usize,
+    extended_header: bool,
+    offset: Option<std::time::Duration>,
+}
+
+impl Codec {
+    /// Creates a new [`Codec`] instance.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum amount of data in a frame being decoded. Decoding a
+    /// frame whose header claims more data than this fails with an error
+    /// of kind [`std::io::ErrorKind::InvalidData`], rather than buffering
+    /// that much data. Defaults to 8 MiB.
+    ///
+    /// Since the header is read from an untrusted stream, this bounds the
+    /// amount of memory that a single frame can cause to be allocated.
+    pub fn set_max_frame_length(&mut self, max_frame_length: usize) {
+        self.max_frame_length = max_frame_length;
+    }
+
+    /// Sets how timestamps are rounded when encoding frames.
+    ///
+    /// See [`Encoder::set_rounding`](crate::Encoder::set_rounding).
+    pub fn set_rounding(&mut self, rounding: crate::Rounding) {
+        self.encoder.set_rounding(rounding);
+    }
+
+    /// Sets whether extended headers are used for encoding frames with
+    /// timestamps which don't fit in an ordinary header, and recognized
+    /// when decoding frames. Defaults to `false`.
+    ///
+    /// See
+    /// [`Encoder::set_extended_header`](crate::Encoder::set_extended_header)
+    /// and [`Parser::set_extended_header`](crate::Parser::set_extended_header).
+    pub fn set_extended_header(&mut self, extended: bool) {
+        self.encoder.set_extended_header(extended);
+        self.extended_header = extended;
+    }
+
+    /// Sets the maximum amount of data written in a single frame when
+    /// encoding.
+    ///
+    /// See
+    /// [`Encoder::set_max_frame_size`](crate::Encoder::set_max_frame_size).
+    pub fn set_max_frame_size(&mut self, max_frame_size: Option<usize>) {
+        self.encoder.set_max_frame_size(max_frame_size);
+    }
+
+    /// How much the timestamps in the decoded stream should be offset by.
+    ///
+    /// See [`Parser::offset`](crate::Parser::offset).
+    #[must_use]
+    pub fn offset(&self) -> Option<std::time::Duration> {
+        self.offset
+    }
+}
+
+impl Default for Codec {
+    fn default() -> Self {
+        Self {
+            encoder: crate::encoder::Encoder::new(),
+            max_frame_length: 8 * 1024 * 1024,
+            extended_header: false,
+            offset: None,
+        }
+    }
+}
+
+impl tokio_util::codec::Decoder for Codec {
+    type Item = crate::frame::Frame;
+    type Error = std::io::Error;
+
+    // this unwrap isn't reachable
+    #[allow(clippy::missing_panics_doc)]
+    fn decode(
+        &mut self,
+        src: &mut bytes::BytesMut,
+    ) -> std::io::Result<Option<Self::Item>> {
+        use crate::parser::Header;
+
+        if src.len() < Header::LEN {
+            return Ok(None);
+        }
+        let header = Header::parse(
+            // unwrap is safe because of the length check above
+            src[..Header::LEN].try_into().unwrap(),
+        );
+        if header.len() > self.max_frame_length {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "frame of {} bytes exceeds the limit of {} bytes",
+                    header.len(),
+                    self.max_frame_length
+                ),
+            ));
+        }
+        let len = Header::LEN + header.len();
+        if src.len() < len {
+            src.reserve(len - src.len());
+            return Ok(None);
+        }
+
+        let bytes = src.split_to(len);
+        let mut data = &bytes[Header::LEN..];
+        let extended_time = self
+            .extended_header
+            .then(|| header.extended_time(data))
+            .flatten();
+        let time = if let Some(time) = extended_time {
+            data = &data[crate::parser::EXTENDED_LEN..];
+            time
+        } else {
+            header.check().map_err(|e| {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+            })?;
+            header.time()
+        };
+        if self.offset.is_none() {
+            self.offset = Some(time);
+        }
+        Ok(Some(crate::frame::Frame {
+            time,
+            data: data.to_vec(),
+        }))
+    }
+}
+
+impl tokio_util::codec::Encoder<crate::frame::Frame> for Codec {
+    type Error = std::io::Error;
+
+    fn encode(
+        &mut self,
+        frame: crate::frame::Frame,
+        dst: &mut bytes::BytesMut,
+    ) -> std::io::Result<()> {
+        dst.reserve(self.encoder.encoded_len(&frame));
+        self.encoder.encode_buf(&frame, dst).map_err(|e| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, e)
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+    use tokio_util::codec::{Decoder as _, Encoder as _};
+
+    #[test]
+    fn test_basic() {
+        let bytes = vec![
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 38, 0, 0, 0, 64, 226, 1, 0,
+            10, 0, 0, 0, 27, 91, 50, 74, 102, 111, 111, 98, 97, 114,
+        ];
+        let frames = vec![
+            crate::frame::Frame {
+                time: std::time::Duration::new(0, 0),
+                data: vec![],
+            },
+            crate::frame::Frame {
+                time: std::time::Duration::new(38, 123_456_000),
+                data: b"\x1b[2Jfoobar".to_vec(),
+            },
+        ];
+
+        let mut codec = Codec::new();
+        let mut src = bytes::BytesMut::new();
+        let mut got = vec![];
+        for c in &bytes {
+            src.extend_from_slice(&[*c]);
+            if let Some(frame) = codec.decode(&mut src).unwrap() {
+                got.push(frame);
+            }
+        }
+        assert_eq!(got, frames);
+        assert!(src.is_empty());
+        assert_eq!(codec.offset(), Some(std::time::Duration::new(0, 0)));
+
+        let mut dst = bytes::BytesMut::new();
+        for frame in frames {
+            codec.encode(frame, &mut dst).unwrap();
+        }
+        assert_eq!(&dst[..], &bytes[..]);
+    }
+
+    #[test]
+    fn test_max_frame_length() {
+        let mut codec = Codec::new();
+        codec.set_max_frame_length(10);
+        let mut src = bytes::BytesMut::from(
+            &[0, 0, 0, 0, 0, 0, 0, 0, 10, 0, 0, 0, b'a'][..],
+        );
+        assert!(codec.decode(&mut src).unwrap().is_none());
+        src.extend_from_slice(b"aaaaaaaaa");
+        assert_eq!(codec.decode(&mut src).unwrap().unwrap().data.len(), 10);
+
+        // a header claiming ~4GiB of data must not be buffered
+        let mut src = bytes::BytesMut::from(
+            &[0, 0, 0, 0, 0, 0, 0, 0, 255, 255, 255, 255][..],
+        );
+        let err = codec.decode(&mut src).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(src.capacity() < 1024);
+
+        let mut src =
+            bytes::BytesMut::from(&[0, 0, 0, 0, 0, 0, 0, 0, 11, 0, 0, 0][..]);
+        assert!(codec.decode(&mut src).is_err());
+    }
+
+    #[test]
+    fn test_extended_header() {
+        let frames = vec![
+            crate::frame::Frame {
+                time: std::time::Duration::new(u64::from(u32::MAX), 1_500),
+                data: b"foo".to_vec(),
+            },
+            crate::frame::Frame {
+                time: std::time::Duration::new(5_000_000_000, 6_500),
+                data: b"bar".to_vec(),
+            },
+        ];
+
+        let mut codec = Codec::new();
+        let mut dst = bytes::BytesMut::new();
+        assert_eq!(
+            codec
+                .encode(frames[1].clone(), &mut dst)
+                .unwrap_err()
+                .kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+
+        codec.set_extended_header(true);
+        codec.set_rounding(crate::Rounding::Nearest);
+        for frame in &frames {
+            codec.encode(frame.clone(), &mut dst).unwrap();
+        }
+        let mut got = vec![];
+        while let Some(frame) = codec.decode(&mut dst).unwrap() {
+            got.push(frame);
+        }
+        assert_eq!(
+            got,
+            vec![
+                crate::frame::Frame {
+                    time: std::time::Duration::new(
+                        u64::from(u32::MAX),
+                        2_000
+                    ),
+                    data: b"foo".to_vec(),
+                },
+                crate::frame::Frame {
+                    time: std::time::Duration::new(5_000_000_000, 7_000),
+                    data: b"bar".to_vec(),
+                },
+            ]
+        );
+        assert!(dst.is_empty());
+
+        // without extended headers, the marker is rejected as invalid
+        let mut encoder = Codec::new();
+        encoder.set_extended_header(true);
+        encoder.encode(frames[1].clone(), &mut dst).unwrap();
+        assert_eq!(
+            Codec::new().decode(&mut dst).unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+    }
+}
@@ -0,0 +1,502 @@
+// how many bytes before and after the first difference are included when
+// comparing the concatenated output
+const CONTEXT_BEFORE: usize = 16;
+const CONTEXT_AFTER: usize = 32;
+
+/// How a [`Comparer`] decides whether two recordings match.
+// this is non_exhaustive (unlike the other enums in this crate) because the
+// Screen variant only exists with the "screen" feature, and features are
+// additive: another crate in the same build enabling it would otherwise
+// break exhaustive matches in code which was written without it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum CompareMode {
+    /// Each frame must contain exactly the same data as the corresponding
+    /// frame in the other recording.
+    #[default]
+    Frames,
+
+    /// The concatenated output of each recording must be identical, but it
+    /// may be split into frames differently. This is useful when comparing
+    /// recordings of a program running under a pty, where the way output is
+    /// split into reads is not deterministic.
+    Bytes,
+
+    /// After each frame, the screen of a terminal emulator of the given
+    /// size must look the same (including colors and other attributes) as
+    /// it does after the corresponding frame in the other recording.
+    /// Differences in the escape sequences used to draw the screen are
+    /// ignored. Window resizes recorded as
+    /// [`ExtendedFrame`](crate::ExtendedFrame)s are applied to the emulated
+    /// terminal.
+    #[cfg(feature = "screen")]
+    Screen { rows: u16, cols: u16 },
+}
+
+/// The first difference between two recordings found by a [`Comparer`].
+///
+/// The [`Display`](std::fmt::Display) implementation produces a
+/// human-readable description of the difference, suitable for test failure
+/// messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// Index of the frame in the actual recording where the recordings
+    /// diverge (or the number of frames in the actual recording, if it
+    /// ended early).
+    pub index: usize,
+
+    /// How the recordings differ.
+    pub kind: DivergenceKind,
+}
+
+impl std::fmt::Display for Divergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "frame {}: ", self.index)?;
+        match &self.kind {
+            DivergenceKind::Data {
+                offset,
+                expected,
+                actual,
+            } => {
+                writeln!(f, "output differs")?;
+                let prefix = expected[..*offset].escape_ascii().count();
+                writeln!(f, "  expected: \"{}\"", expected.escape_ascii())?;
+                writeln!(f, "  actual:   \"{}\"", actual.escape_ascii())?;
+                write!(f, "             {}^", " ".repeat(prefix))
+            }
+            DivergenceKind::Timing { expected, actual } => write!(
+                f,
+                "timestamp differs (expected {expected:?}, got {actual:?})"
+            ),
+            DivergenceKind::Missing => {
+                write!(f, "expected a frame, but the recording ended")
+            }
+            DivergenceKind::Unexpected => {
+                write!(f, "expected the recording to end, but got a frame")
+            }
+            #[cfg(feature = "screen")]
+            DivergenceKind::Screen { expected, actual } => {
+                if expected == actual {
+                    return write!(
+                        f,
+                        "screen contents match, but their attributes differ"
+                    );
+                }
+                write!(f, "screen contents differ")?;
+                let mut expected = expected.split('\n');
+                let mut actual = actual.split('\n');
+                loop {
+                    match (expected.next(), actual.next()) {
+                        (Some(expected), Some(actual))
+                            if expected == actual =>
+                        {
+                            write!(f, "\n  {expected}")?;
+                        }
+                        (None, None) => break,
+                        (expected, actual) => {
+                            if let Some(expected) = expected {
+                                write!(f, "\n- {expected}")?;
+                            }
+                            if let Some(actual) = actual {
+                                write!(f, "\n+ {actual}")?;
+                            }
+                        }
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The ways in which recordings can differ, as reported by a [`Comparer`].
+// non_exhaustive because of the feature gated Screen variant, as with
+// CompareMode
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DivergenceKind {
+    /// The output differs. `offset` is the index of the first differing byte
+    /// within `expected` and `actual`. With [`CompareMode::Frames`] these
+    /// are the full contents of the differing frames, and with
+    /// [`CompareMode::Bytes`] they are a short excerpt of the output
+    /// surrounding the difference.
+    Data {
+        offset: usize,
+        expected: Vec<u8>,
+        actual: Vec<u8>,
+    },
+
+    /// The frame was written at a different time relative to the start of
+    /// the recording, by more than the configured tolerance (see
+    /// [`Comparer::set_timing_tolerance`]).
+    Timing {
+        expected: std::time::Duration,
+        actual: std::time::Duration,
+    },
+
+    /// The actual recording has fewer frames than the expected recording.
+    Missing,
+
+    /// The actual recording has more frames than the expected recording.
+    Unexpected,
+
+    /// The emulated screen differs. Contains the text contents of each
+    /// screen, with rows separated by newlines.
+    #[cfg(feature = "screen")]
+    Screen { expected: String, actual: String },
+}
+
+/// Compares two recordings, such as a recording of a program under test
+/// against a previously recorded golden file.
+///
+/// Recordings can be compared frame by frame, by their concatenated
+/// output, or by what they draw on the screen (see [`CompareMode`]), and
+/// timestamps can optionally be required to match within a tolerance.
+/// Timestamps are compared relative to the first frame of each recording,
+/// so recordings with absolute timestamps can be compared as well.
+#[derive(Debug, Clone, Default)]
+pub struct Comparer {
+    mode: CompareMode,
+    timing_tolerance: Option<std::time::Duration>,
+}
+
+impl Comparer {
+    /// Creates a new [`Comparer`] using the given comparison mode.
+    #[must_use]
+    pub fn new(mode: CompareMode) -> Self {
+        Self {
+            mode,
+            timing_tolerance: None,
+        }
+    }
+
+    /// Sets how far apart timestamps in the two recordings can be. With
+    /// [`CompareMode::Bytes`], this applies to the timestamps of the
+    /// frames containing each byte of output. Defaults to [`None`], which
+    /// ignores timestamps entirely.
+    pub fn set_timing_tolerance(
+        &mut self,
+        tolerance: Option<std::time::Duration>,
+    ) {
+        self.timing_tolerance = tolerance;
+    }
+
+    /// Compares the actual recording against the expected recording,
+    /// returning the first difference found, if any.
+    #[must_use]
+    pub fn compare(
+        &self,
+        expected: &[crate::frame::Frame],
+        actual: &[crate::frame::Frame],
+    ) -> Option<Divergence> {
+        match self.mode {
+            CompareMode::Frames => self.compare_frames(expected, actual),
+            CompareMode::Bytes => self.compare_bytes(expected, actual),
+            #[cfg(feature = "screen")]
+            CompareMode::Screen { rows, cols } => {
+                self.compare_screens(expected, actual, rows, cols)
+            }
+        }
+    }
+
+    fn compare_frames(
+        &self,
+        expected: &[crate::frame::Frame],
+        actual: &[crate::frame::Frame],
+    ) -> Option<Divergence> {
+        for (index, pair) in pairs(expected, actual).enumerate() {
+            let (expected_frame, actual_frame) = match pair {
+                Ok(pair) => pair,
+                Err(kind) => return Some(Divergence { index, kind }),
+            };
+            if expected_frame.data != actual_frame.data {
+                let offset =
+                    common_prefix(&expected_frame.data, &actual_frame.data);
+                return Some(Divergence {
+                    index,
+                    kind: DivergenceKind::Data {
+                        offset,
+                        expected: expected_frame.data.clone(),
+                        actual: actual_frame.data.clone(),
+                    },
+                });
+            }
+            if let Some(kind) = self.compare_times(
+                expected,
+                actual,
+                expected_frame,
+                actual_frame,
+            ) {
+                return Some(Divergence { index, kind });
+            }
+        }
+        None
+    }
+
+    fn compare_bytes(
+        &self,
+        expected: &[crate::frame::Frame],
+        actual: &[crate::frame::Frame],
+    ) -> Option<Divergence> {
+        let expected_bytes: Vec<_> = bytes(expected).collect();
+        let actual_bytes: Vec<_> = bytes(actual).collect();
+        let expected_data: Vec<u8> =
+            expected_bytes.iter().map(|(byte, _)| *byte).collect();
+        let actual_data: Vec<u8> =
+            actual_bytes.iter().map(|(byte, _)| *byte).collect();
+
+        let len = common_prefix(&expected_data, &actual_data);
+        for (expected_index, actual_index) in expected_bytes
+            .iter()
+            .zip(actual_bytes.iter())
+            .map(|((_, e), (_, a))| (*e, *a))
+            .take(len)
+        {
+            if let Some(kind) = self.compare_times(
+                expected,
+                actual,
+                &expected[expected_index],
+                &actual[actual_index],
+            ) {
+                return Some(Divergence {
+                    index: actual_index,
+                    kind,
+                });
+            }
+        }
+
+        if expected_data == actual_data {
+            return None;
+        }
+        // the frame containing the first differing byte, or the frame after
+        // the last one if the actual output ended early
+        let index = actual_bytes
+            .get(len)
+            .map_or(actual.len(), |(_, index)| *index);
+        let start = len.saturating_sub(CONTEXT_BEFORE);
+        let excerpt = |data: &[u8]| {
+            data[start..data.len().min(len + CONTEXT_AFTER)].to_vec()
+        };
+        Some(Divergence {
+            index,
+            kind: DivergenceKind::Data {
+                offset: len - start,
+                expected: excerpt(&expected_data),
+                actual: excerpt(&actual_data),
+            },
+        })
+    }
+
+    #[cfg(feature = "screen")]
+    fn compare_screens(
+        &self,
+        expected: &[crate::frame::Frame],
+        actual: &[crate::frame::Frame],
+        rows: u16,
+        cols: u16,
+    ) -> Option<Divergence> {
+        let mut expected_parser = vt100::Parser::new(rows, cols, 0);
+        let mut actual_parser = vt100::Parser::new(rows, cols, 0);
+        for (index, pair) in pairs(expected, actual).enumerate() {
+            let (expected_frame, actual_frame) = match pair {
+                Ok(pair) => pair,
+                Err(kind) => return Some(Divergence { index, kind }),
+            };
+            process(&mut expected_parser, expected_frame);
+            process(&mut actual_parser, actual_frame);
+            let expected_screen = expected_parser.screen();
+            let actual_screen = actual_parser.screen();
+            if expected_screen.contents_formatted()
+                != actual_screen.contents_formatted()
+                || expected_screen.size() != actual_screen.size()
+            {
+                return Some(Divergence {
+                    index,
+                    kind: DivergenceKind::Screen {
+                        expected: expected_screen.contents(),
+                        actual: actual_screen.contents(),
+                    },
+                });
+            }
+            if let Some(kind) = self.compare_times(
+                expected,
+                actual,
+                expected_frame,
+                actual_frame,
+            ) {
+                return Some(Divergence { index, kind });
+            }
+        }
+        None
+    }
+
+    fn compare_times(
+        &self,
+        expected: &[crate::frame::Frame],
+        actual: &[crate::frame::Frame],
+        expected_frame: &crate::frame::Frame,
+        actual_frame: &crate::frame::Frame,
+    ) -> Option<DivergenceKind> {
+        let tolerance = self.timing_tolerance?;
+        let expected_time = relative_time(expected, expected_frame);
+        let actual_time = relative_time(actual, actual_frame);
+        let difference = expected_time.abs_diff(actual_time);
+        (difference > tolerance).then_some(DivergenceKind::Timing {
+            expected: expected_time,
+            actual: actual_time,
+        })
+    }
+}
+
+// yields corresponding frames from each recording, followed by an error if
+// one recording has more frames than the other
+fn pairs<'a>(
+    expected: &'a [crate::frame::Frame],
+    actual: &'a [crate::frame::Frame],
+) -> impl Iterator<
+    Item = Result<
+        (&'a crate::frame::Frame, &'a crate::frame::Frame),
+        DivergenceKind,
+    >,
+> {
+    let len = expected.len().max(actual.len());
+    (0..len).map(move |i| match (expected.get(i), actual.get(i)) {
+        (Some(expected), Some(actual)) => Ok((expected, actual)),
+        (Some(_), None) => Err(DivergenceKind::Missing),
+        _ => Err(DivergenceKind::Unexpected),
+    })
+}
+
+// yields each byte of output along with the index of the frame it was in
+fn bytes(
+    frames: &[crate::frame::Frame],
+) -> impl Iterator<Item = (u8, usize)> + '_ {
+    frames
+        .iter()
+        .enumerate()
+        .flat_map(|(i, frame)| frame.data.iter().map(move |byte| (*byte, i)))
+}
+
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+fn relative_time(
+    frames: &[crate::frame::Frame],
+    frame: &crate::frame::Frame,
+) -> std::time::Duration {
+    frames
+        .first()
+        .map_or(frame.time, |first| frame.time.saturating_sub(first.time))
+}
+
+#[cfg(feature = "screen")]
+fn process(parser: &mut vt100::Parser, frame: &crate::frame::Frame) {
+    if crate::extended::is_extension(&frame.data) {
+        if let crate::FrameKind::Resize { rows, cols } =
+            crate::ExtendedFrame::from(frame.clone()).kind
+        {
+            parser.screen_mut().set_size(rows, cols);
+        }
+    }
+    parser.process(&frame.data);
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    fn frames(frames: &[(u64, &[u8])]) -> Vec<crate::frame::Frame> {
+        frames
+            .iter()
+            .map(|(millis, data)| crate::frame::Frame {
+                time: std::time::Duration::from_millis(*millis),
+                data: data.to_vec(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_basic() {
+        let expected = frames(&[(0, b"foo"), (100, b"bar"), (200, b"baz")]);
+
+        let comparer = Comparer::new(CompareMode::Frames);
+        assert_eq!(comparer.compare(&expected, &expected), None);
+        let divergence = comparer
+            .compare(&expected, &frames(&[(0, b"foo"), (100, b"b\x1br")]));
+        assert_eq!(
+            divergence,
+            Some(Divergence {
+                index: 1,
+                kind: DivergenceKind::Data {
+                    offset: 1,
+                    expected: b"bar".to_vec(),
+                    actual: b"b\x1br".to_vec(),
+                },
+            })
+        );
+        assert_eq!(
+            divergence.unwrap().to_string(),
+            "frame 1: output differs\n  \
+             expected: \"bar\"\n  \
+             actual:   \"b\\x1br\"\n              \
+             ^"
+        );
+        assert_eq!(
+            comparer.compare(&expected, &expected[..2]),
+            Some(Divergence {
+                index: 2,
+                kind: DivergenceKind::Missing
+            })
+        );
+
+        // rechunked, and with absolute timestamps
+        let actual = frames(&[
+            (5000, b"fo"),
+            (5000, b"ob"),
+            (5150, b"ar"),
+            (5200, b"baz"),
+        ]);
+        let mut comparer = Comparer::new(CompareMode::Bytes);
+        assert_eq!(comparer.compare(&expected, &actual), None);
+        assert_eq!(
+            comparer.compare(&expected, &frames(&[(0, b"foobarbaa")])),
+            Some(Divergence {
+                index: 0,
+                kind: DivergenceKind::Data {
+                    offset: 8,
+                    expected: b"foobarbaz".to_vec(),
+                    actual: b"foobarbaa".to_vec(),
+                },
+            })
+        );
+        comparer
+            .set_timing_tolerance(Some(std::time::Duration::from_millis(10)));
+        assert_eq!(
+            comparer.compare(&expected, &actual),
+            Some(Divergence {
+                index: 1,
+                kind: DivergenceKind::Timing {
+                    expected: std::time::Duration::from_millis(100),
+                    actual: std::time::Duration::ZERO,
+                },
+            })
+        );
+    }
+
+    #[cfg(feature = "screen")]
+    #[test]
+    fn test_screen() {
+        let expected = frames(&[(0, b"foo\r\n"), (100, b"\x1b[31mbar")]);
+        let actual = frames(&[(0, b"fox\x08o\n\r"), (100, b"\x1b[31mbaz")]);
+        let comparer =
+            Comparer::new(CompareMode::Screen { rows: 3, cols: 10 });
+        let divergence = comparer.compare(&expected, &actual).unwrap();
+        assert_eq!(divergence.index, 1);
+        assert_eq!(
+            divergence.to_string(),
+            "frame 1: screen contents differ\n  foo\n- bar\n+ baz"
+        );
+    }
+}
@@ -4,9 +4,18 @@
 /// [`Frame`](crate::Frame) objects, which can be serialized to bytes using
 /// their `try_from` implementation, and then a ttyrec file can be generated
 /// by concatenating those byte strings.
+///
+/// By default, the first frame is created with a timestamp of zero and each
+/// later frame is timestamped relative to it. Creators made with
+/// [`new_absolute`](Self::new_absolute) or
+/// [`with_start_time`](Self::with_start_time) instead write timestamps
+/// relative to the unix epoch, as the classic `ttyrec` tool does.
 #[derive(Debug, Clone, Default)]
 pub struct Creator {
     base_time: Option<std::time::Instant>,
+    absolute: bool,
+    epoch: Option<std::time::Duration>,
+    last_time: Option<std::time::Duration>,
 }
 
 impl Creator {
@@ -16,6 +25,36 @@ impl Creator {
         Self::default()
     }
 
+    /// Creates a new [`Creator`] instance which writes absolute timestamps.
+    ///
+    /// The wall clock time corresponding to the first frame is used as the
+    /// timestamp of that frame, and later frames are timestamped relative
+    /// to it. Note that only the first frame consults the wall clock, so
+    /// later frame times are still derived from the (monotonic)
+    /// [`std::time::Instant`] values passed in.
+    #[must_use]
+    pub fn new_absolute() -> Self {
+        Self {
+            absolute: true,
+            ..Self::default()
+        }
+    }
+
+    /// Creates a new [`Creator`] instance which writes absolute timestamps,
+    /// using the given wall clock time as the timestamp of the first frame.
+    #[must_use]
+    pub fn with_start_time(start_time: std::time::SystemTime) -> Self {
+        Self {
+            absolute: true,
+            epoch: Some(
+                start_time
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default(),
+            ),
+            ..Self::default()
+        }
+    }
+
     /// Returns a new [`Frame`](crate::Frame) object containing the given
     /// data.
     ///
@@ -36,15 +75,81 @@ impl Creator {
         cur_time: std::time::Instant,
         data: &[u8],
     ) -> crate::frame::Frame {
+        crate::frame::Frame {
+            time: self.time_at(cur_time),
+            data: data.to_vec(),
+        }
+    }
+
+    /// Returns a new [`Frame`](crate::Frame) object containing the given data
+    /// at the given amount of time after the previous frame (or after the
+    /// start of the recording, for the first frame).
+    ///
+    /// This is useful for generating synthetic recordings, since it doesn't
+    /// depend on the current time at all (except for the start time of
+    /// creators made with [`new_absolute`](Self::new_absolute)). Note that
+    /// mixing this with calls to [`frame`](Self::frame) or
+    /// [`frame_at`](Self::frame_at) is not guaranteed to do the correct
+    /// thing.
+    pub fn frame_after(
+        &mut self,
+        delay: std::time::Duration,
+        data: &[u8],
+    ) -> crate::frame::Frame {
+        crate::frame::Frame {
+            time: self.time_after(delay),
+            data: data.to_vec(),
+        }
+    }
+
+    // the timestamp that frame_at would use, for callers which don't need
+    // an owned copy of the data
+    pub(crate) fn time_at(
+        &mut self,
+        cur_time: std::time::Instant,
+    ) -> std::time::Duration {
         let base_time = if let Some(base_time) = &self.base_time {
             base_time
         } else {
+            if self.absolute && self.epoch.is_none() {
+                // cur_time isn't necessarily the current time, so adjust
+                // the current wall clock time by the same amount
+                let behind = std::time::Instant::now()
+                    .saturating_duration_since(cur_time);
+                self.epoch = Some(self.epoch().saturating_sub(behind));
+            }
             self.base_time = Some(cur_time);
             &cur_time
         };
-        crate::frame::Frame {
-            time: cur_time - *base_time,
-            data: data.to_vec(),
+        let time = self.epoch.unwrap_or_default() + (cur_time - *base_time);
+        self.last_time = Some(time);
+        time
+    }
+
+    // the timestamp that frame_after would use
+    pub(crate) fn time_after(
+        &mut self,
+        delay: std::time::Duration,
+    ) -> std::time::Duration {
+        let last_time = if let Some(last_time) = self.last_time {
+            last_time
+        } else {
+            let epoch = self.epoch();
+            self.epoch = Some(epoch);
+            epoch
+        };
+        let time = last_time + delay;
+        self.last_time = Some(time);
+        time
+    }
+
+    fn epoch(&self) -> std::time::Duration {
+        match self.epoch {
+            Some(epoch) => epoch,
+            None if self.absolute => std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default(),
+            None => std::time::Duration::ZERO,
         }
     }
 }
@@ -76,4 +181,64 @@ mod test {
             ],
         );
     }
+
+    #[test]
+    fn test_frame_after() {
+        let mut creator = Creator::new();
+        let times: Vec<_> = [0, 1_500, 0, 250]
+            .into_iter()
+            .map(|millis| {
+                creator
+                    .frame_after(
+                        std::time::Duration::from_millis(millis),
+                        b"",
+                    )
+                    .time
+            })
+            .collect();
+        assert_eq!(
+            times,
+            vec![
+                std::time::Duration::new(0, 0),
+                std::time::Duration::new(1, 500_000_000),
+                std::time::Duration::new(1, 500_000_000),
+                std::time::Duration::new(1, 750_000_000),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_absolute() {
+        let start_time = std::time::UNIX_EPOCH
+            + std::time::Duration::new(1_600_000_000, 500_000_000);
+        let mut creator = Creator::with_start_time(start_time);
+        let base_time = std::time::Instant::now();
+
+        let frame = creator.frame_at(base_time, b"foo");
+        assert_eq!(
+            frame.time,
+            std::time::Duration::new(1_600_000_000, 500_000_000)
+        );
+        let frame = creator
+            .frame_at(base_time + std::time::Duration::from_secs(3), b"bar");
+        assert_eq!(
+            frame.time,
+            std::time::Duration::new(1_600_000_003, 500_000_000)
+        );
+
+        // the epoch is estimated from two separate clocks, so allow some
+        // slack
+        let slack = std::time::Duration::from_secs(1);
+        let before = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap();
+        let frame = Creator::new_absolute().frame(b"foo");
+        let after = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap();
+        assert!(
+            before.saturating_sub(slack) <= frame.time
+                && frame.time <= after + slack
+        );
+    }
 }
@@ -0,0 +1,596 @@
+const MAX_HEADER_LEN: usize =
+    crate::parser::Header::LEN + crate::parser::EXTENDED_LEN;
+
+/// How an [`Encoder`] rounds timestamps which are more precise than the
+/// microsecond precision supported by the ttyrec format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rounding {
+    /// Rounds towards zero.
+    #[default]
+    Truncate,
+
+    /// Rounds to the nearest microsecond, with halfway values rounded up.
+    Nearest,
+
+    /// Rounds away from zero.
+    Up,
+}
+
+/// Serializes ttyrec frames.
+///
+/// This is the counterpart to [`Parser`](crate::Parser): rather than
+/// allocating a new buffer for each frame (as the
+/// [`TryFrom<Frame>`](crate::Frame) implementation for [`Vec<u8>`] does),
+/// it writes the serialized frames into a buffer provided by the caller,
+/// which allows many frames to be batched into a single write.
+#[derive(Debug, Default, Clone)]
+pub struct Encoder {
+    rounding: Rounding,
+    extended_header: bool,
+    max_frame_size: Option<usize>,
+}
+
+impl Encoder {
+    /// Creates a new [`Encoder`] instance.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how timestamps are rounded to the microsecond precision of the
+    /// ttyrec format. Defaults to [`Rounding::Truncate`].
+    pub fn set_rounding(&mut self, rounding: Rounding) {
+        self.rounding = rounding;
+    }
+
+    /// Sets whether frames with timestamps of more than [`u32::MAX`]
+    /// seconds should be written with an extended header, rather than
+    /// failing with [`Error::FrameTooLong`](crate::Error::FrameTooLong).
+    /// Defaults to `false`. Frames with smaller timestamps are always
+    /// written with an ordinary header.
+    ///
+    /// An extended header has its seconds field set to [`u32::MAX`] and its
+    /// microseconds field set to [`u32::MAX`] (which is out of range for an
+    /// ordinary header, so the two can't be confused), and is followed by
+    /// the actual number of seconds as a little-endian 64-bit integer and
+    /// the actual number of microseconds as a little-endian 32-bit integer,
+    /// which are counted as part of the frame data. This keeps the stream
+    /// readable by classic ttyrec players (which will show those twelve
+    /// bytes as output), but a [`Parser`](crate::Parser) will only decode
+    /// the timestamp correctly if
+    /// [`Parser::set_extended_header`](crate::Parser::set_extended_header)
+    /// is enabled. This is only needed for absolute timestamps after the
+    /// year 2106.
+    pub fn set_extended_header(&mut self, extended: bool) {
+        self.extended_header = extended;
+    }
+
+    /// Sets the maximum number of bytes of data in a single frame (not
+    /// counting the header). Frames with more data than this are split into
+    /// several frames with the same timestamp. Defaults to [`None`], which
+    /// writes each frame as a single frame, failing with
+    /// [`Error::FrameTooBig`](crate::Error::FrameTooBig) if it contains more
+    /// than [`u32::MAX`] bytes.
+    ///
+    /// Where possible, frames are split between UTF-8 characters and
+    /// outside of terminal escape sequences, so that players which process
+    /// each frame separately don't see partial characters or sequences.
+    /// Data is only split in the middle of a character or escape sequence
+    /// if there is no other way to fit it within the limit.
+    /// [`ExtendedFrame`](crate::ExtendedFrame)s are never split, since they
+    /// can't be parsed from partial data.
+    pub fn set_max_frame_size(&mut self, max_frame_size: Option<usize>) {
+        self.max_frame_size = max_frame_size.map(|size| size.max(1));
+    }
+
+    /// Returns the number of bytes that the given frame will take up when
+    /// serialized.
+    #[must_use]
+    pub fn encoded_len(&self, frame: &crate::frame::Frame) -> usize {
+        let header_len = if self.is_extended(frame.time) {
+            MAX_HEADER_LEN
+        } else {
+            crate::parser::Header::LEN
+        };
+        self.chunks(&frame.data)
+            .iter()
+            .map(|chunk| header_len + chunk.len())
+            .sum()
+    }
+
+    /// Appends the serialized form of the given frame to the given buffer.
+    ///
+    /// # Errors
+    /// * [`Error::FrameTooBig`](crate::Error::FrameTooBig): The frame
+    ///   contains too much data to be represented in a ttyrec file.
+    /// * [`Error::FrameTooLong`](crate::Error::FrameTooLong): The frame
+    ///   timestamp is too large to be represented in a ttyrec file.
+    ///
+    /// If an error is returned, the buffer is left unchanged.
+    pub fn encode(
+        &self,
+        frame: &crate::frame::Frame,
+        buf: &mut Vec<u8>,
+    ) -> crate::error::Result<()> {
+        self.encode_data(frame.time, &frame.data, buf)
+    }
+
+    // like encode, but without requiring the data to be owned by a frame
+    pub(crate) fn encode_data(
+        &self,
+        time: std::time::Duration,
+        data: &[u8],
+        buf: &mut Vec<u8>,
+    ) -> crate::error::Result<()> {
+        let chunks = self.chunks(data);
+        buf.reserve(self.check(time, &chunks)?);
+        for chunk in chunks.iter() {
+            let (header, header_len) = self.header(time, chunk.len())?;
+            buf.extend_from_slice(&header[..header_len]);
+            buf.extend_from_slice(chunk);
+        }
+        Ok(())
+    }
+
+    /// Writes the serialized form of the given frame to the start of the
+    /// given slice, returning the number of bytes written.
+    ///
+    /// # Errors
+    /// * [`Error::BufferTooSmall`](crate::Error::BufferTooSmall): The slice
+    ///   is not large enough to hold the serialized frame.
+    /// * [`Error::FrameTooBig`](crate::Error::FrameTooBig): The frame
+    ///   contains too much data to be represented in a ttyrec file.
+    /// * [`Error::FrameTooLong`](crate::Error::FrameTooLong): The frame
+    ///   timestamp is too large to be represented in a ttyrec file.
+    ///
+    /// If an error is returned, the slice is left unchanged.
+    pub fn encode_to_slice(
+        &self,
+        frame: &crate::frame::Frame,
+        buf: &mut [u8],
+    ) -> crate::error::Result<usize> {
+        let chunks = self.chunks(&frame.data);
+        let len = self.check(frame.time, &chunks)?;
+        if buf.len() < len {
+            return Err(crate::error::Error::BufferTooSmall {
+                needed: len,
+                available: buf.len(),
+            });
+        }
+        let mut pos = 0;
+        for chunk in chunks.iter() {
+            let (header, header_len) =
+                self.header(frame.time, chunk.len())?;
+            buf[pos..pos + header_len].copy_from_slice(&header[..header_len]);
+            pos += header_len;
+            buf[pos..pos + chunk.len()].copy_from_slice(chunk);
+            pos += chunk.len();
+        }
+        Ok(len)
+    }
+
+    /// Writes the serialized form of the given frame to the given
+    /// [`bytes::BufMut`] instance.
+    ///
+    /// # Errors
+    /// * [`Error::BufferTooSmall`](crate::Error::BufferTooSmall): The buffer
+    ///   does not have enough remaining capacity to hold the serialized
+    ///   frame.
+    /// * [`Error::FrameTooBig`](crate::Error::FrameTooBig): The frame
+    ///   contains too much data to be represented in a ttyrec file.
+    /// * [`Error::FrameTooLong`](crate::Error::FrameTooLong): The frame
+    ///   timestamp is too large to be represented in a ttyrec file.
+    ///
+    /// If an error is returned, the buffer is left unchanged.
+    #[cfg(feature = "bytes")]
+    pub fn encode_buf<B: bytes::BufMut>(
+        &self,
+        frame: &crate::frame::Frame,
+        buf: &mut B,
+    ) -> crate::error::Result<()> {
+        let chunks = self.chunks(&frame.data);
+        let len = self.check(frame.time, &chunks)?;
+        if buf.remaining_mut() < len {
+            return Err(crate::error::Error::BufferTooSmall {
+                needed: len,
+                available: buf.remaining_mut(),
+            });
+        }
+        for chunk in chunks.iter() {
+            let (header, header_len) =
+                self.header(frame.time, chunk.len())?;
+            buf.put_slice(&header[..header_len]);
+            buf.put_slice(chunk);
+        }
+        Ok(())
+    }
+
+    // makes sure that every frame the data will be split into can be
+    // encoded (so that errors are returned before anything is written), and
+    // returns the total encoded length
+    fn check(
+        &self,
+        time: std::time::Duration,
+        chunks: &Chunks<'_>,
+    ) -> crate::error::Result<usize> {
+        let mut len = 0;
+        for chunk in chunks.iter() {
+            let (_, header_len) = self.header(time, chunk.len())?;
+            len += header_len + chunk.len();
+        }
+        Ok(len)
+    }
+
+    // the data for each frame that the given data will be written as
+    fn chunks<'a>(&self, data: &'a [u8]) -> Chunks<'a> {
+        let ends = self
+            .max_frame_size
+            .filter(|&max| {
+                data.len() > max && !crate::extended::is_extension(data)
+            })
+            .map_or_else(Vec::new, |max| split_points(data, max));
+        Chunks { data, ends }
+    }
+
+    fn is_extended(&self, time: std::time::Duration) -> bool {
+        self.extended_header && self.round(time).0 > u64::from(u32::MAX)
+    }
+
+    // returns the rounded timestamp as seconds and microseconds
+    fn round(&self, time: std::time::Duration) -> (u64, u32) {
+        let nanos = time.subsec_nanos() % 1_000;
+        let round_up = match self.rounding {
+            Rounding::Truncate => false,
+            Rounding::Nearest => nanos >= 500,
+            Rounding::Up => nanos > 0,
+        };
+        let micros = time.subsec_micros() + u32::from(round_up);
+        if micros == 1_000_000 {
+            (time.as_secs().saturating_add(1), 0)
+        } else {
+            (time.as_secs(), micros)
+        }
+    }
+
+    // the header, followed by the extended seconds if needed, along with
+    // how many bytes of it are used
+    fn header(
+        &self,
+        time: std::time::Duration,
+        data_len: usize,
+    ) -> crate::error::Result<([u8; MAX_HEADER_LEN], usize)> {
+        let (secs, micros) = self.round(time);
+        let extended = self.is_extended(time);
+        let (header_secs, header_micros, len) = if extended {
+            (
+                u32::MAX,
+                crate::parser::EXTENDED_MICROS,
+                data_len.checked_add(crate::parser::EXTENDED_LEN),
+            )
+        } else {
+            (
+                u32::try_from(secs).map_err(|_| {
+                    crate::error::Error::FrameTooLong { input: secs }
+                })?,
+                micros,
+                Some(data_len),
+            )
+        };
+        let len = len
+            .and_then(|len| u32::try_from(len).ok())
+            .ok_or(crate::error::Error::FrameTooBig { input: data_len })?;
+        let mut header = [0; MAX_HEADER_LEN];
+        header[..4].copy_from_slice(&header_secs.to_le_bytes());
+        header[4..8].copy_from_slice(&header_micros.to_le_bytes());
+        header[8..12].copy_from_slice(&len.to_le_bytes());
+        if extended {
+            header[12..20].copy_from_slice(&secs.to_le_bytes());
+            header[20..].copy_from_slice(&micros.to_le_bytes());
+            Ok((header, MAX_HEADER_LEN))
+        } else {
+            Ok((header, crate::parser::Header::LEN))
+        }
+    }
+}
+
+// data split into the chunks which will each be written as a frame. the
+// split points are found once, and then reused for both checking and
+// writing the frames.
+struct Chunks<'a> {
+    data: &'a [u8],
+    // the end of each chunk, or empty if the data isn't split
+    ends: Vec<usize>,
+}
+
+impl<'a> Chunks<'a> {
+    fn iter(&self) -> impl Iterator<Item = &'a [u8]> + '_ {
+        let data = self.data;
+        let mut ends = self.ends.iter();
+        let mut start = Some(0);
+        std::iter::from_fn(move || {
+            let chunk_start = start?;
+            let end = if self.ends.is_empty() {
+                data.len()
+            } else {
+                *ends.next()?
+            };
+            start = (end < data.len()).then_some(end);
+            Some(&data[chunk_start..end])
+        })
+    }
+}
+
+// returns the end of each chunk of the given data, where each chunk is the
+// longest run of at most max bytes which doesn't end in the middle of a
+// utf8 character or an escape sequence, or exactly max bytes if there is no
+// such run. the parser state is carried across chunks, so chunks which
+// start in the middle of a sequence are still handled correctly.
+fn split_points(data: &[u8], max: usize) -> Vec<usize> {
+    let mut stripper = crate::strip::Stripper::new();
+    let mut continuation = 0_u8;
+    let mut start = 0;
+    let mut split = None;
+    let mut ends = vec![];
+    for (i, &c) in data.iter().enumerate() {
+        if i - start == max {
+            let end = split.unwrap_or(i);
+            ends.push(end);
+            start = end;
+            split = None;
+        }
+        stripper.keep(c);
+        continuation = match c {
+            0x80..=0xbf => continuation.saturating_sub(1),
+            0xc0..=0xdf => 1,
+            0xe0..=0xef => 2,
+            0xf0..=0xf7 => 3,
+            _ => 0,
+        };
+        if continuation == 0 && stripper.is_ground() {
+            split = Some(i + 1);
+        }
+    }
+    ends.push(data.len());
+    ends
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_basic() {
+        let frames = vec![
+            crate::frame::Frame {
+                time: std::time::Duration::new(0, 0),
+                data: vec![],
+            },
+            crate::frame::Frame {
+                time: std::time::Duration::new(38, 123_456_000),
+                data: b"\x1b[2Jfoobar".to_vec(),
+            },
+        ];
+        let expected = vec![
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 38, 0, 0, 0, 64, 226, 1, 0,
+            10, 0, 0, 0, 27, 91, 50, 74, 102, 111, 111, 98, 97, 114,
+        ];
+
+        let encoder = Encoder::new();
+        let mut buf = vec![];
+        for frame in &frames {
+            encoder.encode(frame, &mut buf).unwrap();
+        }
+        assert_eq!(buf, expected);
+        assert_eq!(
+            frames.iter().map(|f| encoder.encoded_len(f)).sum::<usize>(),
+            expected.len()
+        );
+
+        let mut buf = [0; 40];
+        let mut len = 0;
+        for frame in &frames {
+            len += encoder.encode_to_slice(frame, &mut buf[len..]).unwrap();
+        }
+        assert_eq!(&buf[..len], &expected[..]);
+        assert!(matches!(
+            encoder.encode_to_slice(&frames[1], &mut buf[..21]),
+            Err(crate::error::Error::BufferTooSmall {
+                needed: 22,
+                available: 21
+            })
+        ));
+    }
+
+    #[test]
+    fn test_rounding() {
+        let frame = |secs, nanos| crate::frame::Frame {
+            time: std::time::Duration::new(secs, nanos),
+            data: vec![],
+        };
+        let header = |encoder: &Encoder, frame| {
+            let mut buf = vec![];
+            encoder.encode(&frame, &mut buf).unwrap();
+            let mut parser = crate::Parser::new();
+            parser.add_bytes(&buf);
+            parser.try_next_frame().unwrap().unwrap().time
+        };
+
+        let mut encoder = Encoder::new();
+        for (rounding, expected) in [
+            (Rounding::Truncate, [1_000_001, 1_000_001, 1_999_999]),
+            (Rounding::Nearest, [1_000_001, 1_000_002, 2_000_000]),
+            (Rounding::Up, [1_000_002, 1_000_002, 2_000_000]),
+        ] {
+            encoder.set_rounding(rounding);
+            for (nanos, expected) in
+                [1_499, 1_500, 999_999_999].into_iter().zip(expected)
+            {
+                assert_eq!(
+                    header(&encoder, frame(1, nanos)),
+                    std::time::Duration::from_micros(expected),
+                    "{rounding:?} {nanos}"
+                );
+            }
+        }
+
+        encoder.set_rounding(Rounding::Up);
+        assert!(matches!(
+            encoder.encode(
+                &frame(u64::from(u32::MAX), 999_999_001),
+                &mut vec![]
+            ),
+            Err(crate::error::Error::FrameTooLong { .. })
+        ));
+    }
+
+    #[test]
+    fn test_extended() {
+        let frames = vec![
+            crate::frame::Frame {
+                time: std::time::Duration::new(4_000_000_000, 5_000),
+                data: b"foo".to_vec(),
+            },
+            crate::frame::Frame {
+                time: std::time::Duration::new(5_000_000_000, 6_000),
+                data: b"bar".to_vec(),
+            },
+        ];
+
+        let mut encoder = Encoder::new();
+        assert!(matches!(
+            encoder.encode(&frames[1], &mut vec![]),
+            Err(crate::error::Error::FrameTooLong {
+                input: 5_000_000_000
+            })
+        ));
+
+        encoder.set_extended_header(true);
+        let mut buf = vec![];
+        for frame in &frames {
+            encoder.encode(frame, &mut buf).unwrap();
+        }
+        assert_eq!(
+            buf.len(),
+            frames.iter().map(|f| encoder.encoded_len(f)).sum::<usize>()
+        );
+        // the first frame doesn't need an extended header
+        assert_eq!(encoder.encoded_len(&frames[0]), 15);
+        assert_eq!(&buf[15..23], &[0xff; 8]);
+
+        let mut parser = crate::Parser::new();
+        parser.add_bytes(&buf);
+        assert_eq!(parser.next_frame().unwrap(), frames[0]);
+        assert_eq!(
+            parser.next_frame().unwrap().data,
+            b"\x00\xf2\x05\x2a\x01\x00\x00\x00\x06\x00\x00\x00bar"
+        );
+        let mut parser = crate::Parser::new();
+        parser.add_bytes(&buf);
+        parser.try_next_frame().unwrap();
+        assert!(matches!(
+            parser.try_next_frame(),
+            Err(crate::Error::InvalidMicros { input: u32::MAX })
+        ));
+
+        let mut parser = crate::Parser::new();
+        parser.set_extended_header(true);
+        parser.add_bytes(&buf);
+        let got: Vec<_> =
+            std::iter::from_fn(|| parser.next_frame()).collect();
+        assert_eq!(got, frames);
+
+        // a timestamp of exactly u32::MAX seconds is an ordinary header,
+        // with or without extended headers enabled, and isn't mistaken for
+        // an extended one
+        let frame = crate::frame::Frame {
+            time: std::time::Duration::new(u64::from(u32::MAX), 7_000),
+            data: b"\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00"
+                .to_vec(),
+        };
+        for extended in [false, true] {
+            encoder.set_extended_header(extended);
+            let mut buf = vec![];
+            encoder.encode(&frame, &mut buf).unwrap();
+            assert_eq!(buf.len(), 12 + frame.data.len());
+            let mut parser = crate::Parser::new();
+            parser.set_extended_header(true);
+            parser.add_bytes(&buf);
+            assert_eq!(parser.try_next_frame().unwrap().unwrap(), frame);
+        }
+    }
+
+    #[test]
+    fn test_max_frame_size() {
+        let split = |encoder: &Encoder, data: &[u8]| {
+            let frame = crate::frame::Frame {
+                time: std::time::Duration::new(1, 2_000),
+                data: data.to_vec(),
+            };
+            let mut buf = vec![];
+            encoder.encode(&frame, &mut buf).unwrap();
+            assert_eq!(buf.len(), encoder.encoded_len(&frame));
+            let mut parser = crate::Parser::new();
+            parser.add_bytes(&buf);
+            std::iter::from_fn(|| parser.next_frame())
+                .map(|got| {
+                    assert_eq!(got.time, frame.time);
+                    got.data
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut encoder = Encoder::new();
+        assert_eq!(split(&encoder, b"foobar"), [b"foobar"]);
+        encoder.set_max_frame_size(Some(4));
+        assert_eq!(split(&encoder, b""), [b""]);
+        assert_eq!(split(&encoder, b"foobar"), [&b"foob"[..], b"ar"]);
+        assert_eq!(
+            split(&encoder, "ab\u{e9}\u{e9}".as_bytes()),
+            [&b"ab\xc3\xa9"[..], b"\xc3\xa9"]
+        );
+        assert_eq!(
+            split(&encoder, "ab\u{2603}".as_bytes()),
+            [&b"ab"[..], b"\xe2\x98\x83"]
+        );
+        assert_eq!(
+            split(&encoder, b"ab\x1b[mc\x1b[31md"),
+            [&b"ab"[..], b"\x1b[mc", b"\x1b[31", b"md"]
+        );
+        assert_eq!(
+            split(&encoder, b"\x1b]0;title\x07"),
+            [&b"\x1b]0;"[..], b"titl", b"e\x07"]
+        );
+        // the escape sequence state carries over from one chunk to the next
+        assert_eq!(
+            split(&encoder, b"\x1b]0;ab\x1b[31mc"),
+            [&b"\x1b]0;"[..], b"ab\x1b[", b"31mc"]
+        );
+
+        // extended frames can't be parsed from partial data
+        encoder.set_max_frame_size(Some(1));
+        let extended = crate::frame::Frame::from(crate::ExtendedFrame {
+            time: std::time::Duration::from_secs(1),
+            kind: crate::FrameKind::Resize { rows: 24, cols: 80 },
+        });
+        assert_eq!(
+            split(&encoder, &extended.data),
+            std::slice::from_ref(&extended.data)
+        );
+
+        let mut buf = [0; 20];
+        assert!(matches!(
+            encoder.encode_to_slice(
+                &crate::frame::Frame {
+                    time: std::time::Duration::ZERO,
+                    data: b"ab".to_vec(),
+                },
+                &mut buf,
+            ),
+            Err(crate::error::Error::BufferTooSmall {
+                needed: 26,
+                available: 20
+            })
+        ));
+    }
+}
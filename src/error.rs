@@ -1,6 +1,18 @@
 /// Errors potentially returned by this crate.
 #[derive(Debug)]
 pub enum Error {
+    /// failed to accept a new connection
+    Accept { source: std::io::Error },
+
+    /// buffer too small: N bytes are required to serialize the frame, but
+    /// only M bytes are available
+    BufferTooSmall { needed: usize, available: usize },
+
+    /// failed to encode image
+    Encode {
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
     /// eof
     EOF,
 
@@ -12,9 +24,22 @@ pub enum Error {
     /// at most M seconds
     FrameTooLong { input: u64 },
 
+    /// invalid ttyrec frame: the microseconds field was N, but must be less
+    /// than 1000000
+    InvalidMicros { input: u32 },
+
     /// failed to read from input
     Read { source: std::io::Error },
 
+    /// the followed file was replaced
+    Rotated,
+
+    /// failed to spawn command
+    Spawn { source: std::io::Error },
+
+    /// the followed file was truncated
+    Truncated,
+
     /// failed to write to output
     Write { source: std::io::Error },
 }
@@ -22,24 +47,45 @@ pub enum Error {
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Self::Accept { source } => {
+                write!(f, "failed to accept a new connection: {source}")
+            }
+            Self::BufferTooSmall { needed, available } => write!(
+                f,
+                "buffer too small: {needed} bytes are required to serialize \
+                the frame, but only {available} bytes are available"
+            ),
+            Self::Encode { source } => {
+                write!(f, "failed to encode image: {source}")
+            }
             Self::EOF => write!(f, "eof"),
             Self::FrameTooBig { input } => write!(
                 f,
                 "failed to create ttyrec frame: got {} bytes of data, but \
                 ttyrec frames can be at most {} bytes",
                 input,
-                u32::max_value()
+                u32::MAX
             ),
             Self::FrameTooLong { input } => write!(
                 f,
                 "failed to create ttyrec frame: got {} seconds, but ttyrecs \
                 can be at most {} seconds",
                 input,
-                u32::max_value()
+                u32::MAX
+            ),
+            Self::InvalidMicros { input } => write!(
+                f,
+                "invalid ttyrec frame: the microseconds field was {input}, \
+                but must be less than 1000000"
             ),
             Self::Read { source } => {
                 write!(f, "failed to read from input: {source}")
             }
+            Self::Rotated => write!(f, "the followed file was replaced"),
+            Self::Spawn { source } => {
+                write!(f, "failed to spawn command: {source}")
+            }
+            Self::Truncated => write!(f, "the followed file was truncated"),
             Self::Write { source } => {
                 write!(f, "failed to write to output: {source}")
             }
@@ -50,7 +96,11 @@ impl std::fmt::Display for Error {
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            Self::Read { source } | Self::Write { source } => Some(source),
+            Self::Accept { source }
+            | Self::Read { source }
+            | Self::Spawn { source }
+            | Self::Write { source } => Some(source),
+            Self::Encode { source } => Some(&**source),
             _ => None,
         }
     }
@@ -0,0 +1,206 @@
+const PREFIX: &[u8] = b"\x1b_ttyrec;";
+const SUFFIX: &[u8] = b"\x1b\\";
+
+/// The contents of an [`ExtendedFrame`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameKind {
+    /// Bytes emitted by the terminal, as in a standard ttyrec frame.
+    Output(Vec<u8>),
+
+    /// Bytes typed by the user.
+    Input(Vec<u8>),
+
+    /// The terminal was resized.
+    Resize { rows: u16, cols: u16 },
+
+    /// A named point in the recording.
+    Marker(String),
+
+    /// Arbitrary metadata about the recording.
+    Metadata { key: String, value: String },
+}
+
+/// A ttyrec frame which can hold information other than terminal output.
+///
+/// Standard ttyrec files can only hold terminal output. Extended frames of
+/// other kinds are stored as standard frames whose data consists of a
+/// single APC escape sequence, which terminals (and therefore classic
+/// ttyrec players) ignore. The sequence has the form
+/// `ESC _ ttyrec;<kind>;<args> ESC \`, where the kinds are:
+///
+/// * `input;<data>`
+/// * `resize;<rows>;<cols>`
+/// * `marker;<label>`
+/// * `meta;<key>;<value>`
+///
+/// Within `<data>`, `<label>`, `<key>`, and `<value>`, the bytes `%` and
+/// `;`, and any bytes outside of the printable ASCII range, are
+/// percent-encoded (as `%XX`, with `XX` being the byte value in
+/// hexadecimal).
+///
+/// Extended frames can be converted to and from standard
+/// [`Frame`](crate::Frame) values using [`From`], and can be read directly
+/// with [`Parser::next_extended_frame`](crate::Parser::next_extended_frame).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtendedFrame {
+    /// Amount of time passed since the start of the ttyrec file.
+    ///
+    /// See [`Frame::time`](crate::Frame::time).
+    pub time: std::time::Duration,
+
+    /// The contents of the frame.
+    pub kind: FrameKind,
+}
+
+impl From<ExtendedFrame> for crate::frame::Frame {
+    fn from(frame: ExtendedFrame) -> Self {
+        let mut data = vec![];
+        match frame.kind {
+            FrameKind::Output(output) => {
+                return Self {
+                    time: frame.time,
+                    data: output,
+                }
+            }
+            FrameKind::Input(input) => {
+                data.extend_from_slice(b"input;");
+                escape(&input, &mut data);
+            }
+            FrameKind::Resize { rows, cols } => {
+                data.extend_from_slice(
+                    format!("resize;{rows};{cols}").as_bytes(),
+                );
+            }
+            FrameKind::Marker(label) => {
+                data.extend_from_slice(b"marker;");
+                escape(label.as_bytes(), &mut data);
+            }
+            FrameKind::Metadata { key, value } => {
+                data.extend_from_slice(b"meta;");
+                escape(key.as_bytes(), &mut data);
+                data.push(b';');
+                escape(value.as_bytes(), &mut data);
+            }
+        }
+        let mut bytes = PREFIX.to_vec();
+        bytes.extend_from_slice(&data);
+        bytes.extend_from_slice(SUFFIX);
+        Self {
+            time: frame.time,
+            data: bytes,
+        }
+    }
+}
+
+impl From<crate::frame::Frame> for ExtendedFrame {
+    /// Frames which don't contain a valid extension sequence are treated as
+    /// [`FrameKind::Output`].
+    fn from(frame: crate::frame::Frame) -> Self {
+        let kind =
+            parse(&frame.data).unwrap_or(FrameKind::Output(frame.data));
+        Self {
+            time: frame.time,
+            kind,
+        }
+    }
+}
+
+/// Returns whether the given frame data is an extension sequence (as
+/// opposed to terminal output).
+pub fn is_extension(data: &[u8]) -> bool {
+    body(data).is_some()
+}
+
+fn body(data: &[u8]) -> Option<&[u8]> {
+    let body = data.strip_prefix(PREFIX)?.strip_suffix(SUFFIX)?;
+    // the sequence would have been terminated early otherwise
+    if body.contains(&b'\x1b') {
+        return None;
+    }
+    Some(body)
+}
+
+fn parse(data: &[u8]) -> Option<FrameKind> {
+    let mut fields = body(data)?.split(|&c| c == b';');
+    let kind = fields.next()?;
+    let args: Vec<_> = fields.collect();
+    match (kind, &args[..]) {
+        (b"input", [input]) => Some(FrameKind::Input(unescape(input)?)),
+        (b"resize", [rows, cols]) => Some(FrameKind::Resize {
+            rows: std::str::from_utf8(rows).ok()?.parse().ok()?,
+            cols: std::str::from_utf8(cols).ok()?.parse().ok()?,
+        }),
+        (b"marker", [label]) => {
+            Some(FrameKind::Marker(String::from_utf8(unescape(label)?).ok()?))
+        }
+        (b"meta", [key, value]) => Some(FrameKind::Metadata {
+            key: String::from_utf8(unescape(key)?).ok()?,
+            value: String::from_utf8(unescape(value)?).ok()?,
+        }),
+        _ => None,
+    }
+}
+
+fn escape(bytes: &[u8], out: &mut Vec<u8>) {
+    for &c in bytes {
+        if matches!(c, b' '..=b'~') && c != b'%' && c != b';' {
+            out.push(c);
+        } else {
+            out.extend_from_slice(format!("%{c:02X}").as_bytes());
+        }
+    }
+}
+
+fn unescape(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut out = vec![];
+    let mut iter = bytes.iter();
+    while let Some(&c) = iter.next() {
+        if c == b'%' {
+            let hex = [*iter.next()?, *iter.next()?];
+            out.push(
+                u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16)
+                    .ok()?,
+            );
+        } else {
+            out.push(c);
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_basic() {
+        let kinds = vec![
+            FrameKind::Output(b"\x1b[2Jfoobar".to_vec()),
+            FrameKind::Input(b"ls -l\r\x1b[A%;".to_vec()),
+            FrameKind::Resize { rows: 24, cols: 80 },
+            FrameKind::Marker("build finished \u{2713}".to_string()),
+            FrameKind::Metadata {
+                key: "user".to_string(),
+                value: "doy".to_string(),
+            },
+        ];
+        for kind in kinds {
+            let extended = ExtendedFrame {
+                time: std::time::Duration::new(38, 123_456_000),
+                kind,
+            };
+            let frame = crate::frame::Frame::from(extended.clone());
+            assert_eq!(
+                is_extension(&frame.data),
+                !matches!(extended.kind, FrameKind::Output(_))
+            );
+            assert_eq!(ExtendedFrame::from(frame), extended);
+        }
+
+        let frame = crate::frame::Frame::from(ExtendedFrame {
+            time: std::time::Duration::new(0, 0),
+            kind: FrameKind::Resize { rows: 24, cols: 80 },
+        });
+        assert_eq!(frame.data, b"\x1b_ttyrec;resize;24;80\x1b\\");
+    }
+}
@@ -0,0 +1,184 @@
+/// Options for following a ttyrec file which is still being written.
+///
+/// See [`blocking::Reader::set_follow`](crate::blocking::Reader::set_follow)
+/// and (with the `"follow"` feature) `Reader::set_follow`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FollowOptions {
+    /// How long to wait before checking for more data after reaching the
+    /// end of the input stream. Defaults to 250ms.
+    pub poll_interval: std::time::Duration,
+
+    /// How long to wait for more data before giving up and returning
+    /// [`Error::EOF`](crate::Error::EOF). Defaults to [`None`], which waits
+    /// forever.
+    pub timeout: Option<std::time::Duration>,
+}
+
+impl Default for FollowOptions {
+    fn default() -> Self {
+        Self {
+            poll_interval: std::time::Duration::from_millis(250),
+            timeout: None,
+        }
+    }
+}
+
+// how many of the most recently read bytes are remembered, in order to
+// detect a file which was truncated and then grew past the read position
+const TAIL_LEN: usize = 64;
+
+/// Tracks the state of a file being followed by path, in order to detect
+/// when it is truncated or replaced.
+#[derive(Debug, Clone)]
+pub struct Watch {
+    path: std::path::PathBuf,
+    pos: u64,
+    id: Option<(u64, u64)>,
+    tail: Vec<u8>,
+}
+
+impl Watch {
+    pub fn new(path: &std::path::Path, metadata: &std::fs::Metadata) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            pos: 0,
+            id: file_id(metadata),
+            tail: vec![],
+        }
+    }
+
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    /// Records that the given data was read from the file.
+    pub fn advance(&mut self, data: &[u8]) {
+        self.pos += u64::try_from(data.len()).unwrap_or(u64::MAX);
+        self.tail
+            .extend_from_slice(&data[data.len().saturating_sub(TAIL_LEN)..]);
+        let excess = self.tail.len().saturating_sub(TAIL_LEN);
+        self.tail.drain(..excess);
+    }
+
+    /// Compares the current metadata for the watched path against the
+    /// state of the file that is currently open, returning an error
+    /// describing what happened if the file has been truncated or replaced.
+    pub fn check(&self, metadata: &std::fs::Metadata) -> crate::Result<()> {
+        if self.id.is_some() && file_id(metadata) != self.id {
+            Err(crate::Error::Rotated)
+        } else if metadata.len() < self.pos {
+            Err(crate::Error::Truncated)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The offset and length of the most recently read data, which should
+    /// be read back from the path and passed to
+    /// [`check_tail`](Self::check_tail).
+    pub fn tail(&self) -> (u64, usize) {
+        let len = self.tail.len();
+        (self.pos - u64::try_from(len).unwrap_or(0), len)
+    }
+
+    /// Compares the data currently at the position given by
+    /// [`tail`](Self::tail) against what was originally read from there.
+    /// [`check`](Self::check) only notices a truncated file while it is
+    /// still shorter than the read position, so this catches files which
+    /// were truncated and then written past that position again.
+    pub fn check_tail(
+        &self,
+        tail: std::io::Result<Vec<u8>>,
+    ) -> crate::Result<()> {
+        match tail {
+            Ok(tail) if tail == self.tail => Ok(()),
+            Ok(_) => Err(crate::Error::Truncated),
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                Err(crate::Error::Truncated)
+            }
+            // the file may have been moved away without having been
+            // replaced yet
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(source) => Err(crate::Error::Read { source }),
+        }
+    }
+
+    /// Resets the tracked state after the path has been reopened.
+    pub fn reset(&mut self, metadata: &std::fs::Metadata) {
+        self.pos = 0;
+        self.id = file_id(metadata);
+        self.tail.clear();
+    }
+}
+
+// the return value is only ever None on non-unix platforms
+#[cfg(unix)]
+#[allow(clippy::unnecessary_wraps)]
+fn file_id(metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt as _;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+    use std::io::Write as _;
+
+    #[test]
+    fn test_basic() {
+        let path = std::env::temp_dir()
+            .join(format!("ttyrec-follow-{}", std::process::id()));
+        let mut fh = std::fs::File::create(&path).unwrap();
+        let mut creator = crate::Creator::new();
+        let mut write = |fh: &mut std::fs::File, data: &[u8]| {
+            let frame = creator.frame_after(std::time::Duration::ZERO, data);
+            let bytes: Vec<u8> = frame.try_into().unwrap();
+            fh.write_all(&bytes).unwrap();
+        };
+
+        write(&mut fh, b"foo");
+        let mut reader = crate::blocking::Reader::follow_path(
+            &path,
+            FollowOptions {
+                poll_interval: std::time::Duration::from_millis(10),
+                timeout: Some(std::time::Duration::from_millis(50)),
+            },
+        )
+        .unwrap();
+        assert_eq!(reader.read_frame().unwrap().data, b"foo");
+        assert!(matches!(reader.read_frame(), Err(crate::Error::EOF)));
+
+        write(&mut fh, b"bar");
+        assert_eq!(reader.read_frame().unwrap().data, b"bar");
+
+        let mut fh = std::fs::File::create(&path).unwrap();
+        write(&mut fh, b"baz");
+        assert!(matches!(reader.read_frame(), Err(crate::Error::Truncated)));
+        assert_eq!(reader.read_frame().unwrap().data, b"baz");
+
+        // truncated and then written past the previous read position
+        let mut fh = std::fs::File::create(&path).unwrap();
+        write(&mut fh, b"regrown");
+        assert!(matches!(reader.read_frame(), Err(crate::Error::Truncated)));
+        assert_eq!(reader.read_frame().unwrap().data, b"regrown");
+
+        std::fs::remove_file(&path).unwrap();
+        let mut fh = std::fs::File::create(&path).unwrap();
+        write(&mut fh, b"quux");
+        if cfg!(unix) {
+            assert!(matches!(
+                reader.read_frame(),
+                Err(crate::Error::Rotated)
+            ));
+        }
+        assert_eq!(reader.read_frame().unwrap().data, b"quux");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,230 @@
+// an embedded 5x8 bitmap font (including one row for descenders), drawn
+// into 6x10 cells to leave space between characters and lines. box drawing
+// and block element characters are generated rather than stored, since
+// they need to connect with the neighboring cells.
+
+pub const WIDTH: usize = 6;
+pub const HEIGHT: usize = 10;
+
+// the row in which underlines are drawn
+pub const UNDERLINE: usize = 9;
+
+// where the lines of box drawing characters meet
+const CENTER_ROW: usize = 4;
+const CENTER_COL: u8 = 0b00_1000;
+
+// each glyph is 8 rows of 5 bits, with the top row in the highest bits
+#[allow(clippy::unreadable_literal)]
+const ASCII: [u64; 95] = [
+    0x0000000000, // space
+    0x2108420080, // !
+    0x5294000000, // "
+    0x52beafa940, // #
+    0x23e8e2f880, // $
+    0xc644444c60, // %
+    0x64a88ac9a0, // &
+    0x2108000000, // '
+    0x1110841040, // (
+    0x4104211100, // )
+    0x012aea9000, // *
+    0x0109f21000, // +
+    0x0000001088, // ,
+    0x0001f00000, // -
+    0x0000003180, // .
+    0x0844444200, // /
+    0x74675cc5c0, // 0
+    0x23084211c0, // 1
+    0x74422223e0, // 2
+    0xf88820c5c0, // 3
+    0x11952f8840, // 4
+    0xfc3c10c5c0, // 5
+    0x3221e8c5c0, // 6
+    0xf844442100, // 7
+    0x7462e8c5c0, // 8
+    0x7462f08980, // 9
+    0x0318063000, // :
+    0x0318063100, // ;
+    0x1111041040, // <
+    0x003e0f8000, // =
+    0x4104111100, // >
+    0x7442220080, // ?
+    0x7442dad5c0, // @
+    0x7463f8c620, // A
+    0xf463e8c7c0, // B
+    0x74610845c0, // C
+    0xe4a318cb80, // D
+    0xfc21e843e0, // E
+    0xfc21e84200, // F
+    0x746178c5e0, // G
+    0x8c63f8c620, // H
+    0x71084211c0, // I
+    0x3884214980, // J
+    0x8ca98a4a20, // K
+    0x84210843e0, // L
+    0x8eeb58c620, // M
+    0x8c7359c620, // N
+    0x746318c5c0, // O
+    0xf463e84200, // P
+    0x74631ac9a0, // Q
+    0xf463ea4a20, // R
+    0x7c20e087c0, // S
+    0xf908421080, // T
+    0x8c6318c5c0, // U
+    0x8c6318a880, // V
+    0x8c635ad540, // W
+    0x8c54454620, // X
+    0x8c62a21080, // Y
+    0xf8444443e0, // Z
+    0x72108421c0, // [
+    0x8410410420, // \
+    0x70842109c0, // ]
+    0x22a2000000, // ^
+    0x000000001f, // _
+    0x4104000000, // `
+    0x001c17c5e0, // a
+    0x842d98c7c0, // b
+    0x001d0845c0, // c
+    0x085b38c5e0, // d
+    0x001d1fc1c0, // e
+    0x3251c42100, // f
+    0x001f18bc2e, // g
+    0x842d98c620, // h
+    0x20184211c0, // i
+    0x100c210a4c, // j
+    0x84254c5240, // k
+    0x61084211c0, // l
+    0x00355ac620, // m
+    0x002d98c620, // n
+    0x001d18c5c0, // o
+    0x003d18fa10, // p
+    0x001f18bc21, // q
+    0x002d984200, // r
+    0x001f0707c0, // s
+    0x42388424c0, // t
+    0x002318cda0, // u
+    0x002318a880, // v
+    0x00231ad540, // w
+    0x0022a22a20, // x
+    0x002318bc2e, // y
+    0x003e2223e0, // z
+    0x1108821040, // {
+    0x2108421080, // |
+    0x4108221100, // }
+    0x0011510000, // ~
+];
+
+// returns the glyph for the given character, as one bitmask per row, with
+// the leftmost pixel in the highest bit
+pub fn glyph(c: char) -> [u8; HEIGHT] {
+    if let Some(glyph) = ascii(c) {
+        return glyph;
+    }
+    if let Some(glyph) = box_drawing(c) {
+        return glyph;
+    }
+    if let Some(glyph) = block(c) {
+        return glyph;
+    }
+    // draw an empty box for characters we don't know about
+    let mut glyph = [0; HEIGHT];
+    glyph[1] = 0b11_1110;
+    for row in &mut glyph[2..8] {
+        *row = 0b10_0010;
+    }
+    glyph[8] = 0b11_1110;
+    glyph
+}
+
+fn ascii(c: char) -> Option<[u8; HEIGHT]> {
+    let index = usize::try_from(u32::from(c).checked_sub(0x20)?).ok()?;
+    let bits = *ASCII.get(index)?;
+    let mut glyph = [0; HEIGHT];
+    for (i, row) in glyph[1..9].iter_mut().enumerate() {
+        let shift = 5 * (7 - i);
+        // the glyph is five pixels wide, leaving the rightmost column of
+        // the cell empty
+        *row = u8::try_from((bits >> shift) & 0x1f).unwrap_or(0) << 1;
+    }
+    Some(glyph)
+}
+
+fn box_drawing(c: char) -> Option<[u8; HEIGHT]> {
+    // (up, down, left, right) - heavy, double, and rounded variants are
+    // drawn the same as the light ones
+    let (up, down, left, right) = match c {
+        '\u{2500}' | '\u{2501}' | '\u{2550}' => (false, false, true, true),
+        '\u{2502}' | '\u{2503}' | '\u{2551}' => (true, true, false, false),
+        '\u{250c}' | '\u{250f}' | '\u{2554}' | '\u{256d}' => {
+            (false, true, false, true)
+        }
+        '\u{2510}' | '\u{2513}' | '\u{2557}' | '\u{256e}' => {
+            (false, true, true, false)
+        }
+        '\u{2514}' | '\u{2517}' | '\u{255a}' | '\u{2570}' => {
+            (true, false, false, true)
+        }
+        '\u{2518}' | '\u{251b}' | '\u{255d}' | '\u{256f}' => {
+            (true, false, true, false)
+        }
+        '\u{251c}' | '\u{2523}' | '\u{2560}' => (true, true, false, true),
+        '\u{2524}' | '\u{252b}' | '\u{2563}' => (true, true, true, false),
+        '\u{252c}' | '\u{2533}' | '\u{2566}' => (false, true, true, true),
+        '\u{2534}' | '\u{253b}' | '\u{2569}' => (true, false, true, true),
+        '\u{253c}' | '\u{254b}' | '\u{256c}' => (true, true, true, true),
+        _ => return None,
+    };
+
+    let mut glyph = [0; HEIGHT];
+    for (i, row) in glyph.iter_mut().enumerate() {
+        if (up && i <= CENTER_ROW) || (down && i >= CENTER_ROW) {
+            *row |= CENTER_COL;
+        }
+    }
+    if left {
+        glyph[CENTER_ROW] |= 0b11_1000;
+    }
+    if right {
+        glyph[CENTER_ROW] |= 0b00_1111;
+    }
+    Some(glyph)
+}
+
+fn block(c: char) -> Option<[u8; HEIGHT]> {
+    let mut glyph = [0; HEIGHT];
+    for (i, row) in glyph.iter_mut().enumerate() {
+        *row = match c {
+            '\u{2588}' => 0b11_1111,
+            '\u{2580}' if i < HEIGHT / 2 => 0b11_1111,
+            '\u{2584}' if i >= HEIGHT / 2 => 0b11_1111,
+            '\u{258c}' => 0b11_1000,
+            '\u{2590}' => 0b00_0111,
+            '\u{2591}' if i % 2 == 0 => 0b10_0010,
+            '\u{2592}' if i % 2 == 0 => 0b10_1010,
+            '\u{2593}' if i % 2 == 0 => 0b11_1111,
+            '\u{2592}' | '\u{2593}' => 0b01_0101,
+            '\u{2580}' | '\u{2584}' | '\u{2591}' => 0,
+            _ => return None,
+        };
+    }
+    Some(glyph)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_basic() {
+        assert_eq!(
+            glyph('A'),
+            [
+                0b00_0000, 0b01_1100, 0b10_0010, 0b10_0010, 0b11_1110,
+                0b10_0010, 0b10_0010, 0b10_0010, 0b00_0000, 0b00_0000,
+            ]
+        );
+        assert_eq!(glyph(' '), [0; HEIGHT]);
+        assert_eq!(glyph('\u{2500}')[4], 0b11_1111);
+        assert_eq!(glyph('\u{2588}'), [0b11_1111; HEIGHT]);
+        assert_ne!(glyph('\u{263a}'), [0; HEIGHT]);
+    }
+}
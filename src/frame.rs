@@ -6,7 +6,8 @@
 /// the actual time that the frame was generated).
 ///
 /// Frame objects are typically created via the [`Creator`](crate::Creator),
-/// [`Parser`](crate::Parser), or [`Reader`](crate::Reader) classes.
+/// [`Parser`](crate::Parser), or
+/// [`blocking::Reader`](crate::blocking::Reader) classes.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Frame {
     /// Amount of time passed since the start of the ttyrec file.
@@ -18,26 +19,21 @@ pub struct Frame {
     pub data: Vec<u8>,
 }
 
+impl Frame {
+    /// Returns the number of bytes that this frame will take up when
+    /// serialized.
+    #[must_use]
+    pub fn encoded_len(&self) -> usize {
+        12 + self.data.len()
+    }
+}
+
 impl TryFrom<Frame> for Vec<u8> {
     type Error = crate::error::Error;
 
     fn try_from(frame: Frame) -> crate::error::Result<Self> {
-        let secs = u32::try_from(frame.time.as_secs()).map_err(|_| {
-            crate::error::Error::FrameTooLong {
-                input: frame.time.as_secs(),
-            }
-        })?;
-        let micros = frame.time.subsec_micros();
-        let len = u32::try_from(frame.data.len()).map_err(|_| {
-            crate::error::Error::FrameTooBig {
-                input: frame.data.len(),
-            }
-        })?;
         let mut bytes = vec![];
-        bytes.extend(secs.to_le_bytes().iter());
-        bytes.extend(micros.to_le_bytes().iter());
-        bytes.extend(len.to_le_bytes().iter());
-        bytes.extend(frame.data.iter());
+        crate::encoder::Encoder::new().encode(&frame, &mut bytes)?;
         Ok(bytes)
     }
 }
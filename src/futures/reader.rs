@@ -0,0 +1,122 @@
+/// Reads ttyrec frames from a [`futures_io::AsyncRead`] instance.
+pub struct Reader<T: futures_io::AsyncRead> {
+    input: T,
+    parser: crate::Parser,
+    buf: [u8; 4096],
+}
+
+impl<T: futures_io::AsyncRead + std::marker::Unpin + Send> Reader<T> {
+    /// Creates a new [`Reader`] from a [`futures_io::AsyncRead`] instance.
+    pub fn new(input: T) -> Self {
+        Self {
+            input,
+            parser: crate::Parser::new(),
+            buf: [0; 4096],
+        }
+    }
+
+    /// Returns the next parsed frame from the input stream.
+    ///
+    /// # Errors
+    /// * [`Error::EOF`](crate::Error::EOF): The input stream has been closed.
+    /// * [`Error::Read`](crate::Error::Read): There was an error reading from
+    ///   the input stream.
+    /// * [`Error::InvalidMicros`](crate::Error::InvalidMicros): The frame
+    ///   header was invalid. The frame has been skipped, and reading can
+    ///   continue with the next frame.
+    // these unwraps aren't reachable
+    #[allow(clippy::missing_panics_doc)]
+    pub async fn read_frame(&mut self) -> crate::Result<crate::Frame> {
+        loop {
+            if let Some(frame) = self.parser.try_next_frame()? {
+                return Ok(frame);
+            }
+            let bytes = std::future::poll_fn(|cx| {
+                std::pin::Pin::new(&mut self.input)
+                    .poll_read(cx, &mut self.buf)
+            })
+            .await
+            .map_err(|source| crate::Error::Read { source })?;
+            if bytes == 0 {
+                return Err(crate::Error::EOF);
+            }
+            self.parser.add_bytes(
+                // read() returning a value means that that many bytes are
+                // guaranteed to be available
+                &self.buf[..bytes],
+            );
+        }
+    }
+
+    /// How much the timestamps in this file should be offset by.
+    ///
+    /// See [`Parser::offset`](crate::Parser::offset).
+    pub fn offset(&self) -> Option<std::time::Duration> {
+        self.parser.offset()
+    }
+
+    /// Sets whether frames written with an extended header should be
+    /// recognized. Defaults to `false`.
+    ///
+    /// See
+    /// [`Parser::set_extended_header`](crate::Parser::set_extended_header).
+    pub fn set_extended_header(&mut self, extended: bool) {
+        self.parser.set_extended_header(extended);
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    struct Input {
+        data: Vec<u8>,
+        pos: usize,
+    }
+
+    impl futures_io::AsyncRead for Input {
+        fn poll_read(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+            buf: &mut [u8],
+        ) -> std::task::Poll<std::io::Result<usize>> {
+            let this = std::pin::Pin::into_inner(self);
+            // only return a few bytes at a time, so that frames are split
+            // across reads
+            let len = buf.len().min(5).min(this.data.len() - this.pos);
+            buf[..len].copy_from_slice(&this.data[this.pos..this.pos + len]);
+            this.pos += len;
+            std::task::Poll::Ready(Ok(len))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_basic() {
+        let frames = [
+            crate::Frame {
+                time: std::time::Duration::from_secs(10),
+                data: b"foo".to_vec(),
+            },
+            crate::Frame {
+                time: std::time::Duration::from_secs(11),
+                data: b"".to_vec(),
+            },
+            crate::Frame {
+                time: std::time::Duration::from_secs(11),
+                data: b"\x1b[2Jbarbaz".to_vec(),
+            },
+        ];
+        let mut data = vec![];
+        for frame in &frames {
+            data.extend(Vec::<u8>::try_from(frame.clone()).unwrap());
+        }
+        let mut reader = Reader::new(Input { data, pos: 0 });
+        assert_eq!(reader.offset(), None);
+        for frame in &frames {
+            assert_eq!(&reader.read_frame().await.unwrap(), frame);
+        }
+        assert!(matches!(reader.read_frame().await, Err(crate::Error::EOF)));
+        assert_eq!(reader.offset(), Some(std::time::Duration::from_secs(10)));
+    }
+}
@@ -0,0 +1,294 @@
+use std::fmt::Write as _;
+
+const STYLE: &str = r"
+body { background: #222; color: #ccc; font-family: sans-serif; }
+#ttyrec-player { display: inline-block; }
+#ttyrec-screen {
+  margin: 0; padding: 4px;
+  font-family: monospace; font-size: 14px; line-height: 1.2;
+}
+#ttyrec-controls { display: flex; align-items: center; gap: 8px; }
+#ttyrec-seek { flex: 1; }
+#ttyrec-time { font-family: monospace; }
+";
+
+// the screens are rendered ahead of time, so the player only has to pick
+// which one to show
+const SCRIPT: &str = r#"
+(function () {
+  "use strict";
+  var data = JSON.parse(document.getElementById("ttyrec-data").textContent);
+  var screen = document.getElementById("ttyrec-screen");
+  var button = document.getElementById("ttyrec-play");
+  var seek = document.getElementById("ttyrec-seek");
+  var clock = document.getElementById("ttyrec-time");
+  var shown = -1, current = 0, playing = false, base = 0;
+  seek.max = data.duration;
+
+  function fmt(t) {
+    var s = Math.floor(t);
+    return Math.floor(s / 60) + ":" + ("0" + s % 60).slice(-2);
+  }
+  function render() {
+    var i = 0;
+    while (i + 1 < data.screens.length && data.screens[i + 1][0] <= current) {
+      i++;
+    }
+    if (i !== shown) {
+      screen.innerHTML = data.screens[i][1].map(function (line) {
+        return data.lines[line];
+      }).join("\n");
+      shown = i;
+    }
+    seek.value = current;
+    clock.textContent = fmt(current) + " / " + fmt(data.duration);
+    button.textContent = playing ? "Pause" : "Play";
+  }
+  function tick(now) {
+    if (!playing) {
+      return;
+    }
+    current = Math.min((now - base) / 1000, data.duration);
+    if (current >= data.duration) {
+      playing = false;
+    } else {
+      requestAnimationFrame(tick);
+    }
+    render();
+  }
+  function toggle() {
+    playing = !playing;
+    if (playing) {
+      if (current >= data.duration) {
+        current = 0;
+      }
+      base = performance.now() - current * 1000;
+      requestAnimationFrame(tick);
+    }
+    render();
+  }
+  button.addEventListener("click", toggle);
+  seek.addEventListener("input", function () {
+    current = parseFloat(seek.value);
+    base = performance.now() - current * 1000;
+    render();
+  });
+  document.addEventListener("keydown", function (e) {
+    if (e.key === " " && e.target !== button) {
+      e.preventDefault();
+      toggle();
+    }
+  });
+  render();
+})();
+"#;
+
+/// Exports a recording as a self-contained HTML page.
+///
+/// Frames are run through a terminal emulator, and each distinct screen
+/// state is embedded in the page as HTML (with identical lines only stored
+/// once), along with a small JavaScript player with play/pause and seeking
+/// controls, so it can be viewed in any browser without network access or
+/// any other tooling. Window resizes recorded as
+/// [`ExtendedFrame`](crate::ExtendedFrame)s are applied during playback.
+///
+/// Frames can come from any source - add them in stream order with
+/// [`add_frame`](Self::add_frame), and then generate the page with
+/// [`to_html`](Self::to_html). Timestamps are made relative to the first
+/// frame, so recordings with absolute timestamps play back correctly.
+pub struct HtmlExporter {
+    title: String,
+    timeline: crate::timeline::Timeline,
+    palette: crate::Palette,
+}
+
+impl HtmlExporter {
+    /// Creates a new [`HtmlExporter`] for a terminal of the given size.
+    #[must_use]
+    pub fn new(rows: u16, cols: u16) -> Self {
+        Self {
+            title: "ttyrec".to_string(),
+            timeline: crate::timeline::Timeline::new(rows, cols),
+            palette: crate::Palette::new(),
+        }
+    }
+
+    /// Sets the title of the generated page. Defaults to `"ttyrec"`.
+    pub fn set_title(&mut self, title: &str) {
+        self.title = title.to_string();
+    }
+
+    /// Sets the colors used to draw the screen.
+    pub fn set_palette(&mut self, palette: crate::Palette) {
+        self.palette = palette;
+    }
+
+    /// Adds the next frame in the stream.
+    pub fn add_frame(&mut self, frame: &crate::frame::Frame) {
+        self.timeline.add_frame(frame);
+    }
+
+    /// Generates the HTML page containing all of the frames added so far.
+    #[must_use]
+    pub fn to_html(&self) -> String {
+        let mut lines: Vec<String> = vec![];
+        let mut line_ids = std::collections::HashMap::new();
+        let mut screens = String::new();
+        let mut duration = std::time::Duration::ZERO;
+        for (parser, start, _) in
+            self.timeline.screens(std::time::Duration::from_millis(1))
+        {
+            let screen = parser.screen();
+            let (rows, cols) = screen.size();
+            if !screens.is_empty() {
+                screens.push(',');
+            }
+            // writing to a String can't fail
+            let _ = write!(screens, "[{:.3},[", start.as_secs_f64());
+            for row in 0..rows {
+                let line = self.line(screen, row, cols);
+                let id = *line_ids.entry(line).or_insert_with_key(|line| {
+                    lines.push(line.clone());
+                    lines.len() - 1
+                });
+                if row > 0 {
+                    screens.push(',');
+                }
+                let _ = write!(screens, "{id}");
+            }
+            screens.push_str("]]");
+            duration = start;
+        }
+
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html>\n<head>\n");
+        html.push_str("<meta charset=\"utf-8\">\n<title>");
+        html.push_str(&crate::markup::escape(&self.title));
+        html.push_str("</title>\n<style>");
+        html.push_str(STYLE);
+        html.push_str("</style>\n</head>\n<body>\n");
+        html.push_str("<div id=\"ttyrec-player\">\n");
+        let _ = writeln!(
+            html,
+            "<pre id=\"ttyrec-screen\" style=\"color:{};background:{}\">\
+             </pre>",
+            crate::markup::hex(self.palette.foreground()),
+            crate::markup::hex(self.palette.background())
+        );
+        html.push_str("<div id=\"ttyrec-controls\">\n");
+        html.push_str("<button id=\"ttyrec-play\">Play</button>\n");
+        html.push_str(
+            "<input id=\"ttyrec-seek\" type=\"range\" min=\"0\" \
+             step=\"0.01\" value=\"0\">\n",
+        );
+        html.push_str("<span id=\"ttyrec-time\"></span>\n");
+        html.push_str("</div>\n</div>\n");
+        html.push_str(
+            "<script id=\"ttyrec-data\" type=\"application/json\">",
+        );
+        let _ = write!(
+            html,
+            "{{\"duration\":{:.3},\"lines\":[",
+            duration.as_secs_f64()
+        );
+        for (i, line) in lines.iter().enumerate() {
+            if i > 0 {
+                html.push(',');
+            }
+            json_string(line, &mut html);
+        }
+        let _ = write!(html, "],\"screens\":[{screens}]}}");
+        html.push_str("</script>\n<script>");
+        html.push_str(SCRIPT);
+        html.push_str("</script>\n</body>\n</html>\n");
+        html
+    }
+
+    // returns the markup for a single row of the screen
+    fn line(&self, screen: &vt100::Screen, row: u16, cols: u16) -> String {
+        let mut runs = crate::markup::runs(screen, row, cols, &self.palette);
+        // trailing blank space doesn't need to be drawn
+        if let Some((_, attrs, text)) = runs.last_mut() {
+            if attrs.bg == self.palette.background() {
+                text.truncate(text.trim_end().len());
+            }
+        }
+
+        let mut line = String::new();
+        for (_, attrs, text) in &runs {
+            let text = crate::markup::escape(text);
+            let mut style = String::new();
+            if attrs.fg != self.palette.foreground() {
+                let _ =
+                    write!(style, "color:{};", crate::markup::hex(attrs.fg));
+            }
+            if attrs.bg != self.palette.background() {
+                let _ = write!(
+                    style,
+                    "background:{};",
+                    crate::markup::hex(attrs.bg)
+                );
+            }
+            if attrs.bold {
+                style.push_str("font-weight:bold;");
+            }
+            if attrs.italic {
+                style.push_str("font-style:italic;");
+            }
+            if attrs.underline {
+                style.push_str("text-decoration:underline;");
+            }
+            if style.is_empty() {
+                line.push_str(&text);
+            } else {
+                let _ = write!(line, "<span style=\"{style}\">{text}</span>");
+            }
+        }
+        line
+    }
+}
+
+// writes a JSON string which is also safe to embed in a <script> element
+fn json_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '<' => out.push_str("\\u003c"),
+            c if c < ' ' => {
+                let _ = write!(out, "\\u{:04x}", u32::from(c));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_basic() {
+        let mut exporter = HtmlExporter::new(3, 10);
+        exporter.set_title("<incident #1>");
+        exporter.add_frame(&crate::frame::Frame {
+            time: std::time::Duration::new(1_686_000_000, 0),
+            data: b"\x1b[?25lfoo".to_vec(),
+        });
+        exporter.add_frame(&crate::frame::Frame {
+            time: std::time::Duration::new(1_686_000_001, 500_000_000),
+            data: b"\r\n\x1b[31ma<b\x1b[m".to_vec(),
+        });
+        let html = exporter.to_html();
+        assert!(html.contains("<title>&lt;incident #1&gt;</title>"));
+        assert!(html.contains(
+            r#"{"duration":1.500,"lines":["foo","","\u003cspan style=\"color:#cd0000;\">a&lt;b\u003c/span>"],"screens":[[0.000,[0,1,1]],[1.500,[0,2,1]]]}"#
+        ));
+        // everything needed to play the recording is embedded
+        assert!(!html.contains("src="));
+        assert!(!html.contains("href="));
+        assert_eq!(html.matches("</script>").count(), 2);
+    }
+}
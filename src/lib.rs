@@ -1,15 +1,69 @@
 //! This crate contains helpers for reading and writing
 //! [ttyrec](https://en.wikipedia.org/wiki/Ttyrec) files.
 //!
-//! `Parser` and `Creator` can be used to read and write files manually, and
+//! `Parser` and `Creator` can be used to read and write files manually
+//! (with `Encoder` serializing frames into caller-provided buffers), and
 //! `Reader` and `Writer` are helpers to provide a nicer API for asynchronous
 //! applications using futures. Additionally, `blocking::Reader` and
 //! `blocking::Writer` provide a similar API for non-asynchronous
 //! applications.
 //!
+//! `Merger` and `blocking::Merger` can be used to interleave frames from
+//! several concurrently recorded files into a single stream, ordered by
+//! their absolute timestamps.
+//!
+//! `Validator` reports timestamp problems (such as timestamps which go
+//! backwards) in a stream of frames, and `Repairer` fixes them.
+//!
+//! `ExtendedFrame` allows recording input, window resizes, markers, and
+//! metadata alongside terminal output, in a way that classic ttyrec players
+//! will ignore.
+//!
+//! `SessionWriter` and `SessionReader` (and their blocking equivalents)
+//! record user input into a separate ttyrec stream alongside the terminal
+//! output, and merge the two back together.
+//!
+//! `Searcher` finds text in a recording, ignoring escape sequences and
+//! frame boundaries, and `Transcript` converts a recording into a plain
+//! text log.
+//!
+//! `RecordingBuilder` constructs recordings with exact timestamps, for use
+//! as test fixtures, and `Comparer` compares a recording against an
+//! expected (golden) recording and reports the first `Divergence` between
+//! them.
+//!
+//! Readers can also follow files which are still being written, similar to
+//! `tail -f` - see `FollowOptions`.
+//!
 //! # Features
 //!
 //! Async support via Tokio can be enabled via the `"async"` feature.
+//! Support for the `futures-io` traits (as used by runtimes such as
+//! `async-std` and `smol`) can be enabled via the `"futures"` feature,
+//! which provides `futures::Reader` and `futures::Writer`. The `"sink"`
+//! feature additionally implements `futures_sink::Sink` for the async
+//! writers. The `"bytes"` feature allows `Encoder` to write to
+//! `bytes::BufMut` instances. The `"codec"` feature provides `Codec`, an
+//! implementation of the `tokio_util::codec` traits for ttyrec frames. The
+//! `"follow"` feature enables follow mode for the Tokio `Reader` (follow mode
+//! for `blocking::Reader` is always available). The `"broadcast"` feature
+//! provides `Broadcaster`, which streams a live recording to multiple viewers
+//! over TCP or unix sockets. The `"redact"` feature provides `Redactor`, for
+//! removing secrets such as passwords and tokens from recordings. The
+//! `"screen"` feature provides `ScreenSearcher`, which searches the rendered
+//! screen contents of a recording rather than the raw output, and
+//! `CompareMode::Screen`, which compares recordings by their rendered screen
+//! contents. The `"html"` feature provides `HtmlExporter`, which converts a
+//! recording into a self-contained HTML page with an embedded player. The
+//! `"gif"` and `"png"` features provide `Animation`, which renders a
+//! recording as an animated GIF or PNG using a built-in terminal emulator and
+//! font, drawn with the colors from a `Palette`. The `"png"` feature also
+//! provides `Thumbnail`, which renders a single screen from a recording as a
+//! PNG image. The `"svg"` feature provides `SvgExporter`, which exports a
+//! recording as an animated SVG image. The `"testing"` feature provides the
+//! `testing` module (on linux), for snapshot testing terminal programs against
+//! golden recordings. The `"proptest"` feature provides the `strategy`
+//! module, with `proptest` strategies for generating frames.
 
 #![warn(clippy::cargo)]
 #![warn(clippy::pedantic)]
@@ -24,20 +78,115 @@
 #![allow(clippy::too_many_lines)]
 #![allow(clippy::type_complexity)]
 
+#[cfg(any(feature = "gif", feature = "png"))]
+mod animation;
+#[cfg(any(feature = "gif", feature = "png"))]
+pub use animation::Animation;
+#[cfg(feature = "broadcast")]
+mod broadcast;
+#[cfg(feature = "broadcast")]
+pub use broadcast::Broadcaster;
+mod buffer;
+#[cfg(feature = "codec")]
+mod codec;
+#[cfg(feature = "codec")]
+pub use codec::Codec;
+mod compare;
+pub use compare::{CompareMode, Comparer, Divergence, DivergenceKind};
 mod creator;
 pub use creator::Creator;
+mod encoder;
+pub use encoder::{Encoder, Rounding};
 mod error;
 pub use error::{Error, Result};
+mod extended;
+pub use extended::{ExtendedFrame, FrameKind};
+mod follow;
+pub use follow::FollowOptions;
+#[cfg(any(feature = "gif", feature = "png"))]
+mod font;
 mod frame;
 pub use frame::Frame;
+#[cfg(feature = "html")]
+mod html;
+#[cfg(feature = "html")]
+pub use html::HtmlExporter;
+#[cfg(any(feature = "html", feature = "svg"))]
+mod markup;
+mod merge;
+pub use merge::MergedFrame;
 mod parser;
 pub use parser::Parser;
+mod rebase;
+pub use rebase::Rebaser;
+mod recording;
+pub use recording::RecordingBuilder;
+#[cfg(feature = "redact")]
+mod redact;
+#[cfg(feature = "redact")]
+pub use redact::{Redaction, Redactor, Replacement, Rule};
+#[cfg(any(
+    feature = "gif",
+    feature = "html",
+    feature = "png",
+    feature = "svg"
+))]
+mod palette;
+#[cfg(any(
+    feature = "gif",
+    feature = "html",
+    feature = "png",
+    feature = "svg"
+))]
+pub use palette::Palette;
+#[cfg(any(feature = "gif", feature = "png"))]
+mod render;
+mod repair;
+pub use repair::{RepairStrategy, Repairer};
+mod search;
+pub use search::{Hit, Searcher};
+#[cfg(feature = "screen")]
+pub use search::{ScreenHit, ScreenSearcher};
+#[cfg(any(test, feature = "proptest"))]
+pub mod strategy;
+mod strip;
+#[cfg(feature = "svg")]
+mod svg;
+#[cfg(feature = "svg")]
+pub use svg::SvgExporter;
+#[cfg(feature = "png")]
+mod thumbnail;
+#[cfg(feature = "png")]
+pub use thumbnail::{Thumbnail, ThumbnailPosition};
+#[cfg(any(
+    feature = "gif",
+    feature = "html",
+    feature = "png",
+    feature = "svg"
+))]
+mod timeline;
+mod transcript;
+pub use transcript::{Line, Transcript};
+mod validate;
+pub use validate::{Diagnostic, DiagnosticKind, Validator};
 pub mod blocking;
+#[cfg(feature = "futures")]
+pub mod futures;
+#[cfg(feature = "async")]
+mod merger;
+#[cfg(all(target_os = "linux", feature = "testing"))]
+pub mod testing;
+#[cfg(feature = "async")]
+pub use merger::Merger;
 #[cfg(feature = "async")]
 mod reader;
 #[cfg(feature = "async")]
 pub use reader::Reader;
 #[cfg(feature = "async")]
+mod session;
+#[cfg(feature = "async")]
+pub use session::{SessionReader, SessionWriter};
+#[cfg(feature = "async")]
 mod writer;
 #[cfg(feature = "async")]
 pub use writer::Writer;
@@ -0,0 +1,76 @@
+// helpers shared by the html and svg exporters
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Attrs {
+    pub fg: [u8; 3],
+    pub bg: [u8; 3],
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+// splits the first `cols` columns of a row of the screen into runs of cells
+// with the same attributes, returning the starting column, attributes, and
+// text of each run. the cursor (if visible) is drawn by swapping the colors
+// of the cell it is on.
+pub fn runs(
+    screen: &vt100::Screen,
+    row: u16,
+    cols: u16,
+    palette: &crate::Palette,
+) -> Vec<(usize, Attrs, String)> {
+    let cursor = (!screen.hide_cursor()).then(|| screen.cursor_position());
+    let mut runs: Vec<(usize, Attrs, String)> = vec![];
+    for col in 0..cols {
+        let Some(cell) = screen.cell(row, col) else {
+            break;
+        };
+        if cell.is_wide_continuation() {
+            continue;
+        }
+        let mut fgcolor = cell.fgcolor();
+        if let vt100::Color::Idx(index @ 0..=7) = fgcolor {
+            if cell.bold() {
+                fgcolor = vt100::Color::Idx(index + 8);
+            }
+        }
+        let mut fg = palette.resolve(fgcolor, palette.foreground());
+        let mut bg = palette.resolve(cell.bgcolor(), palette.background());
+        if cell.inverse() != (cursor == Some((row, col))) {
+            std::mem::swap(&mut fg, &mut bg);
+        }
+        let attrs = Attrs {
+            fg,
+            bg,
+            bold: cell.bold(),
+            italic: cell.italic(),
+            underline: cell.underline(),
+        };
+        let contents = if cell.has_contents() {
+            cell.contents()
+        } else {
+            " "
+        };
+        match runs.last_mut() {
+            Some((_, last, text)) if *last == attrs => {
+                text.push_str(contents);
+            }
+            _ => {
+                runs.push((usize::from(col), attrs, contents.to_string()));
+            }
+        }
+    }
+    runs
+}
+
+pub fn hex(color: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+// escapes text for use in html or xml element contents or attribute values
+pub fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
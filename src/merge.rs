@@ -0,0 +1,124 @@
+/// A frame read from one of the sources of a merged stream.
+///
+/// Returned by [`blocking::Merger`](crate::blocking::Merger) and (with the
+/// `async` feature) `Merger`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergedFrame {
+    /// Index of the source (in the order the sources were given) that this
+    /// frame was read from.
+    pub source: usize,
+
+    /// The frame itself, with its original (absolute) timestamp.
+    pub frame: crate::frame::Frame,
+}
+
+/// Tracks the next pending frame from each source of a merge.
+///
+/// This holds the shared logic for the blocking and async mergers, which
+/// only differ in how they read frames from their sources.
+#[derive(Debug, Clone)]
+pub struct Heads {
+    pending: Vec<Option<crate::frame::Frame>>,
+    done: Vec<bool>,
+    offset: Option<std::time::Duration>,
+}
+
+impl Heads {
+    pub fn new(sources: usize) -> Self {
+        Self {
+            pending: vec![None; sources],
+            done: vec![false; sources],
+            offset: None,
+        }
+    }
+
+    /// Returns the index of a source which needs to have its next frame
+    /// read before the next merged frame can be determined.
+    pub fn needs(&self) -> Option<usize> {
+        self.pending
+            .iter()
+            .zip(self.done.iter())
+            .position(|(pending, done)| pending.is_none() && !done)
+    }
+
+    /// Records the result of reading from the given source. [`None`]
+    /// indicates that the source has been exhausted.
+    pub fn add(&mut self, source: usize, frame: Option<crate::frame::Frame>) {
+        if frame.is_none() {
+            self.done[source] = true;
+        }
+        self.pending[source] = frame;
+    }
+
+    /// Returns the pending frame with the earliest timestamp. Ties are
+    /// broken by source index, so frames with identical timestamps are
+    /// returned in the order their sources were given.
+    pub fn next(&mut self) -> Option<MergedFrame> {
+        let source = self
+            .pending
+            .iter()
+            .enumerate()
+            .filter_map(|(i, frame)| frame.as_ref().map(|f| (f.time, i)))
+            .min()
+            .map(|(_, i)| i)?;
+        let frame = self.pending[source].take()?;
+        if self.offset.is_none() {
+            self.offset = Some(frame.time);
+        }
+        Some(MergedFrame { source, frame })
+    }
+
+    pub fn offset(&self) -> Option<std::time::Duration> {
+        self.offset
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn frame(secs: u64, data: &[u8]) -> crate::frame::Frame {
+        crate::frame::Frame {
+            time: std::time::Duration::from_secs(secs),
+            data: data.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_basic() {
+        let sources = vec![
+            vec![frame(100, b"a1"), frame(103, b"a2"), frame(110, b"a3")],
+            vec![frame(101, b"b1"), frame(103, b"b2")],
+            vec![],
+            vec![frame(99, b"c1")],
+        ];
+        let mut iters: Vec<_> =
+            sources.into_iter().map(IntoIterator::into_iter).collect();
+
+        let mut heads = Heads::new(iters.len());
+        let mut merged = vec![];
+        loop {
+            while let Some(i) = heads.needs() {
+                heads.add(i, iters[i].next());
+            }
+            if let Some(frame) = heads.next() {
+                merged.push((frame.source, frame.frame.data));
+            } else {
+                break;
+            }
+        }
+
+        assert_eq!(
+            merged,
+            vec![
+                (3, b"c1".to_vec()),
+                (0, b"a1".to_vec()),
+                (1, b"b1".to_vec()),
+                (0, b"a2".to_vec()),
+                (1, b"b2".to_vec()),
+                (0, b"a3".to_vec()),
+            ]
+        );
+        assert_eq!(heads.offset(), Some(std::time::Duration::from_secs(99)));
+    }
+}
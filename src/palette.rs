@@ -0,0 +1,132 @@
+/// The colors used when rendering a terminal screen.
+///
+/// Colors are given as `[red, green, blue]`. The first 16 indexed colors
+/// can be customized, and the rest of the 256 color palette is derived the
+/// same way as in xterm. Defaults to xterm's default colors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    colors: [[u8; 3]; 16],
+    foreground: [u8; 3],
+    background: [u8; 3],
+}
+
+impl Palette {
+    /// Creates a new [`Palette`] with the default colors.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets one of the 16 basic indexed colors.
+    ///
+    /// # Panics
+    /// Panics if `index` is not less than 16.
+    pub fn set_color(&mut self, index: usize, color: [u8; 3]) {
+        self.colors[index] = color;
+    }
+
+    /// Sets the default foreground color.
+    pub fn set_foreground(&mut self, color: [u8; 3]) {
+        self.foreground = color;
+    }
+
+    /// Sets the default background color.
+    pub fn set_background(&mut self, color: [u8; 3]) {
+        self.background = color;
+    }
+
+    /// Returns the given indexed color.
+    #[must_use]
+    pub fn color(&self, index: u8) -> [u8; 3] {
+        const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+        match index {
+            0..=15 => self.colors[usize::from(index)],
+            16..=231 => {
+                let i = index - 16;
+                [
+                    LEVELS[usize::from(i / 36)],
+                    LEVELS[usize::from(i / 6 % 6)],
+                    LEVELS[usize::from(i % 6)],
+                ]
+            }
+            232..=255 => {
+                let level = 8 + (index - 232) * 10;
+                [level; 3]
+            }
+        }
+    }
+
+    /// Returns the default foreground color.
+    #[must_use]
+    pub fn foreground(&self) -> [u8; 3] {
+        self.foreground
+    }
+
+    /// Returns the default background color.
+    #[must_use]
+    pub fn background(&self) -> [u8; 3] {
+        self.background
+    }
+
+    pub(crate) fn resolve(
+        &self,
+        color: vt100::Color,
+        default: [u8; 3],
+    ) -> [u8; 3] {
+        match color {
+            vt100::Color::Default => default,
+            vt100::Color::Idx(index) => self.color(index),
+            vt100::Color::Rgb(r, g, b) => [r, g, b],
+        }
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            colors: [
+                [0x00, 0x00, 0x00],
+                [0xcd, 0x00, 0x00],
+                [0x00, 0xcd, 0x00],
+                [0xcd, 0xcd, 0x00],
+                [0x00, 0x00, 0xee],
+                [0xcd, 0x00, 0xcd],
+                [0x00, 0xcd, 0xcd],
+                [0xe5, 0xe5, 0xe5],
+                [0x7f, 0x7f, 0x7f],
+                [0xff, 0x00, 0x00],
+                [0x00, 0xff, 0x00],
+                [0xff, 0xff, 0x00],
+                [0x5c, 0x5c, 0xff],
+                [0xff, 0x00, 0xff],
+                [0x00, 0xff, 0xff],
+                [0xff, 0xff, 0xff],
+            ],
+            foreground: [0xe5, 0xe5, 0xe5],
+            background: [0x00, 0x00, 0x00],
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_basic() {
+        let mut palette = Palette::new();
+        assert_eq!(palette.color(1), [0xcd, 0x00, 0x00]);
+        assert_eq!(palette.color(196), [0xff, 0x00, 0x00]);
+        assert_eq!(palette.color(244), [0x80, 0x80, 0x80]);
+        palette.set_color(1, [0xff, 0x55, 0x55]);
+        palette.set_foreground([0x11, 0x22, 0x33]);
+        assert_eq!(
+            palette.resolve(vt100::Color::Idx(1), palette.foreground()),
+            [0xff, 0x55, 0x55]
+        );
+        assert_eq!(
+            palette.resolve(vt100::Color::Default, palette.foreground()),
+            [0x11, 0x22, 0x33]
+        );
+    }
+}
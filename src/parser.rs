@@ -1,19 +1,59 @@
-#[repr(packed)]
+// the value of the microseconds field which marks an extended header. this
+// is out of range for an ordinary header, so it can't be confused with one.
+pub const EXTENDED_MICROS: u32 = u32::MAX;
+// the length of the seconds and microseconds stored after an extended
+// header
+pub const EXTENDED_LEN: usize = 12;
+
+#[repr(C, packed)]
 #[derive(Debug, Clone, Copy)]
-struct Header {
+pub struct Header {
     secs: u32,
     micros: u32,
     len: u32,
 }
 
 impl Header {
-    fn time(&self) -> std::time::Duration {
+    pub const LEN: usize = std::mem::size_of::<Self>();
+
+    pub fn parse(bytes: [u8; Self::LEN]) -> Self {
+        let [s1, s2, s3, s4, m1, m2, m3, m4, l1, l2, l3, l4] = bytes;
+        Self {
+            secs: u32::from_le_bytes([s1, s2, s3, s4]),
+            micros: u32::from_le_bytes([m1, m2, m3, m4]),
+            len: u32::from_le_bytes([l1, l2, l3, l4]),
+        }
+    }
+
+    pub fn time(&self) -> std::time::Duration {
         std::time::Duration::from_micros(
             u64::from(self.secs) * 1_000_000 + u64::from(self.micros),
         )
     }
 
-    fn len(&self) -> usize {
+    pub fn check(&self) -> crate::Result<()> {
+        if self.micros >= 1_000_000 {
+            return Err(crate::Error::InvalidMicros { input: self.micros });
+        }
+        Ok(())
+    }
+
+    // the timestamp stored at the start of the frame data after an
+    // extended header (see `Encoder::set_extended_header`), if this is one
+    pub fn extended_time(&self, data: &[u8]) -> Option<std::time::Duration> {
+        if self.micros != EXTENDED_MICROS {
+            return None;
+        }
+        let data: &[u8; EXTENDED_LEN] =
+            data.get(..EXTENDED_LEN)?.try_into().ok()?;
+        let [s1, s2, s3, s4, s5, s6, s7, s8, m1, m2, m3, m4] = *data;
+        let secs = u64::from_le_bytes([s1, s2, s3, s4, s5, s6, s7, s8]);
+        let micros = u32::from_le_bytes([m1, m2, m3, m4]);
+        (micros < 1_000_000)
+            .then(|| std::time::Duration::new(secs, micros * 1_000))
+    }
+
+    pub fn len(&self) -> usize {
         usize::try_from(self.len).unwrap_or_else(|_| {
             panic!("this library requires sizeof(usize) to be at least 4")
         })
@@ -33,6 +73,8 @@ pub struct Parser {
     reading: std::collections::VecDeque<u8>,
     read_state: Option<Header>,
     offset: Option<std::time::Duration>,
+    ignore_extensions: bool,
+    extended_header: bool,
 }
 
 impl Parser {
@@ -47,42 +89,104 @@ impl Parser {
         self.reading.extend(bytes.iter());
     }
 
+    /// Sets whether extension frames (see
+    /// [`ExtendedFrame`](crate::ExtendedFrame)) should be skipped by
+    /// [`next_frame`](Self::next_frame). Defaults to `false`, which returns
+    /// them as is, the same way classic ttyrec players see them.
+    pub fn set_ignore_extensions(&mut self, ignore: bool) {
+        self.ignore_extensions = ignore;
+    }
+
+    /// Sets whether frames written with an extended header (see
+    /// [`Encoder::set_extended_header`](crate::Encoder::set_extended_header))
+    /// should be recognized. Defaults to `false`, which returns them as is,
+    /// the same way classic ttyrec players see them (and which causes
+    /// [`try_next_frame`](Self::try_next_frame) to reject them with
+    /// [`Error::InvalidMicros`](crate::Error::InvalidMicros), since extended
+    /// headers are marked by an out of range microseconds field).
+    pub fn set_extended_header(&mut self, extended: bool) {
+        self.extended_header = extended;
+    }
+
     /// Try to read a frame from the internal buffer.
     ///
     /// If a complete frame is found, the bytes for that frame will be removed
     /// from the internal buffer and the frame object will be returned. If a
     /// complete frame is not found, this method will return [`None`].
+    ///
+    /// Frames whose microseconds field is out of range are not rejected, and
+    /// the excess is carried over into the seconds, as classic ttyrec
+    /// players do. Use [`try_next_frame`](Self::try_next_frame) to report
+    /// these as errors instead.
+    pub fn next_frame(&mut self) -> Option<crate::frame::Frame> {
+        loop {
+            let (_, frame) = self.read_frame()?;
+            if !self.ignored(&frame) {
+                return Some(frame);
+            }
+        }
+    }
+
+    /// Try to read a frame from the internal buffer, validating its header.
+    ///
+    /// This behaves the same as [`next_frame`](Self::next_frame), except
+    /// that frames with invalid headers are reported as errors. The invalid
+    /// frame is still removed from the internal buffer, so parsing can
+    /// continue with the following frame.
+    ///
+    /// # Errors
+    /// * [`Error::InvalidMicros`](crate::Error::InvalidMicros): The frame's
+    ///   microseconds field was not less than 1,000,000.
+    pub fn try_next_frame(
+        &mut self,
+    ) -> crate::Result<Option<crate::frame::Frame>> {
+        loop {
+            let Some((valid, frame)) = self.read_frame() else {
+                return Ok(None);
+            };
+            valid?;
+            if !self.ignored(&frame) {
+                return Ok(Some(frame));
+            }
+        }
+    }
+
+    /// Try to read an extended frame from the internal buffer.
+    ///
+    /// This behaves the same as [`next_frame`](Self::next_frame), except
+    /// that extension frames are decoded into their specific kind, rather
+    /// than being returned as terminal output. Extension frames are always
+    /// returned by this method, regardless of the setting of
+    /// [`set_ignore_extensions`](Self::set_ignore_extensions).
+    pub fn next_extended_frame(
+        &mut self,
+    ) -> Option<crate::extended::ExtendedFrame> {
+        self.read_frame().map(|(_, frame)| frame.into())
+    }
+
+    fn ignored(&self, frame: &crate::frame::Frame) -> bool {
+        self.ignore_extensions && crate::extended::is_extension(&frame.data)
+    }
+
     // these unwraps aren't reachable
     #[allow(clippy::missing_panics_doc)]
-    pub fn next_frame(&mut self) -> Option<crate::frame::Frame> {
+    // also returns whether the frame header was valid
+    fn read_frame(
+        &mut self,
+    ) -> Option<(crate::Result<()>, crate::frame::Frame)> {
         let header = if let Some(header) = &self.read_state {
             header
         } else {
-            if self.reading.len() < std::mem::size_of::<Header>() {
+            if self.reading.len() < Header::LEN {
                 return None;
             }
 
-            // these unwraps are guaranteed safe by the length check above
-            let secs1 = self.reading.pop_front().unwrap();
-            let secs2 = self.reading.pop_front().unwrap();
-            let secs3 = self.reading.pop_front().unwrap();
-            let secs4 = self.reading.pop_front().unwrap();
-            let secs = u32::from_le_bytes([secs1, secs2, secs3, secs4]);
-
-            let micros1 = self.reading.pop_front().unwrap();
-            let micros2 = self.reading.pop_front().unwrap();
-            let micros3 = self.reading.pop_front().unwrap();
-            let micros4 = self.reading.pop_front().unwrap();
-            let micros =
-                u32::from_le_bytes([micros1, micros2, micros3, micros4]);
-
-            let len1 = self.reading.pop_front().unwrap();
-            let len2 = self.reading.pop_front().unwrap();
-            let len3 = self.reading.pop_front().unwrap();
-            let len4 = self.reading.pop_front().unwrap();
-            let len = u32::from_le_bytes([len1, len2, len3, len4]);
-
-            let header = Header { secs, micros, len };
+            let mut bytes = [0; Header::LEN];
+            for byte in &mut bytes {
+                // unwrap is safe because of the length check above
+                *byte = self.reading.pop_front().unwrap();
+            }
+            let header = Header::parse(bytes);
             self.read_state = Some(header);
             // unwrap is safe because we just set self.read_state to Some
             self.read_state.as_ref().unwrap()
@@ -101,13 +205,22 @@ impl Parser {
             );
         }
 
-        let time = header.time();
+        let header = *header;
+        let extended_time = self
+            .extended_header
+            .then(|| header.extended_time(&data))
+            .flatten();
+        if extended_time.is_some() {
+            data.drain(..EXTENDED_LEN);
+        }
+        let valid = extended_time.map_or_else(|| header.check(), |_| Ok(()));
+        let time = extended_time.unwrap_or_else(|| header.time());
 
         self.read_state = None;
         if self.offset.is_none() {
             self.offset = Some(time);
         }
-        Some(crate::frame::Frame { time, data })
+        Some((valid, crate::frame::Frame { time, data }))
     }
 
     /// How much the timestamps in this file should be offset by.
@@ -125,6 +238,7 @@ impl Parser {
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod test {
     use super::*;
 
@@ -154,4 +268,119 @@ mod test {
             assert_eq!(got, expected);
         }
     }
+
+    #[test]
+    fn test_extensions() {
+        let frames = vec![
+            crate::frame::Frame {
+                time: std::time::Duration::new(0, 0),
+                data: b"foo".to_vec(),
+            },
+            crate::extended::ExtendedFrame {
+                time: std::time::Duration::new(1, 0),
+                kind: crate::extended::FrameKind::Marker("bar".to_string()),
+            }
+            .into(),
+            crate::frame::Frame {
+                time: std::time::Duration::new(2, 0),
+                data: b"baz".to_vec(),
+            },
+        ];
+        let mut bytes = vec![];
+        for frame in &frames {
+            bytes.extend(Vec::<u8>::try_from(frame.clone()).unwrap());
+        }
+
+        let mut parser = Parser::new();
+        parser.add_bytes(&bytes);
+        let got: Vec<_> =
+            std::iter::from_fn(|| parser.next_frame()).collect();
+        assert_eq!(got, frames);
+
+        let mut parser = Parser::new();
+        parser.set_ignore_extensions(true);
+        parser.add_bytes(&bytes);
+        let got: Vec<_> =
+            std::iter::from_fn(|| parser.next_frame()).collect();
+        assert_eq!(got, vec![frames[0].clone(), frames[2].clone()]);
+
+        let mut parser = Parser::new();
+        parser.add_bytes(&bytes);
+        let got: Vec<_> =
+            std::iter::from_fn(|| parser.next_extended_frame()).collect();
+        assert_eq!(
+            got[1].kind,
+            crate::extended::FrameKind::Marker("bar".to_string())
+        );
+    }
+
+    #[test]
+    fn test_invalid_micros() {
+        let bytes = vec![
+            1, 0, 0, 0, 64, 66, 15, 0, 3, 0, 0, 0, 102, 111, 111, 2, 0, 0, 0,
+            0, 0, 0, 0, 3, 0, 0, 0, 98, 97, 114,
+        ];
+
+        let mut parser = Parser::new();
+        parser.add_bytes(&bytes);
+        assert_eq!(
+            parser.next_frame().unwrap().time,
+            std::time::Duration::new(2, 0)
+        );
+
+        let mut parser = Parser::new();
+        parser.add_bytes(&bytes);
+        assert!(matches!(
+            parser.try_next_frame(),
+            Err(crate::Error::InvalidMicros { input: 1_000_000 })
+        ));
+        assert_eq!(
+            parser.try_next_frame().unwrap(),
+            Some(crate::frame::Frame {
+                time: std::time::Duration::new(2, 0),
+                data: b"bar".to_vec(),
+            })
+        );
+        assert_eq!(parser.try_next_frame().unwrap(), None);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_roundtrip(
+            frames in crate::strategy::frames(0..16),
+            chunks in proptest::collection::vec(1..256_usize, 1..16),
+        ) {
+            let mut bytes = vec![];
+            for frame in &frames {
+                bytes.extend(Vec::<u8>::try_from(frame.clone()).unwrap());
+            }
+
+            let mut parser = Parser::new();
+            let mut got = vec![];
+            let mut rest = &bytes[..];
+            for chunk in chunks.iter().cycle() {
+                if rest.is_empty() {
+                    break;
+                }
+                let (chunk, next) = rest.split_at((*chunk).min(rest.len()));
+                rest = next;
+                parser.add_bytes(chunk);
+                got.extend(std::iter::from_fn(|| parser.next_frame()));
+            }
+            proptest::prop_assert_eq!(got, frames);
+        }
+
+        #[test]
+        fn test_garbage(
+            bytes in proptest::collection::vec(proptest::num::u8::ANY, 0..4096),
+            chunk in 1..256_usize,
+        ) {
+            let mut parser = Parser::new();
+            for chunk in bytes.chunks(chunk) {
+                parser.add_bytes(chunk);
+                while parser.next_extended_frame().is_some() {}
+            }
+            let _ = parser.offset();
+        }
+    }
 }
@@ -1,10 +1,22 @@
 use tokio::io::AsyncReadExt as _;
 
 /// Reads ttyrec frames from a [`tokio::io::AsyncRead`] instance.
+///
+/// By default, reaching the end of the input stream causes
+/// [`read_frame`](Self::read_frame) to return
+/// [`Error::EOF`](crate::Error::EOF). Readers can also be put into follow
+/// mode (like `tail -f`), where they instead wait for more data to be
+/// written - see `set_follow` and `follow_path`. Follow mode is only
+/// available with the `"follow"` feature, and requires the tokio runtime to
+/// have its time driver enabled.
 pub struct Reader<T: tokio::io::AsyncRead> {
     input: T,
     parser: crate::Parser,
     buf: [u8; 4096],
+    #[cfg(feature = "follow")]
+    follow: Option<crate::FollowOptions>,
+    #[cfg(feature = "follow")]
+    watch: Option<(crate::follow::Watch, fn(tokio::fs::File) -> T)>,
 }
 
 impl<T: tokio::io::AsyncRead + std::marker::Unpin + Send> Reader<T> {
@@ -15,20 +27,36 @@ impl<T: tokio::io::AsyncRead + std::marker::Unpin + Send> Reader<T> {
             input,
             parser: crate::Parser::new(),
             buf: [0; 4096],
+            #[cfg(feature = "follow")]
+            follow: None,
+            #[cfg(feature = "follow")]
+            watch: None,
         }
     }
 
     /// Returns the next parsed frame from the input stream.
     ///
     /// # Errors
-    /// * [`Error::EOF`](crate::Error::EOF): The input stream has been closed.
+    /// * [`Error::EOF`](crate::Error::EOF): The input stream has been closed
+    ///   (or in follow mode, no more data was written before the timeout).
     /// * [`Error::Read`](crate::Error::Read): There was an error reading from
-    /// the input stream.
+    ///   the input stream.
+    /// * [`Error::InvalidMicros`](crate::Error::InvalidMicros): The frame
+    ///   header was invalid. The frame has been skipped, and reading can
+    ///   continue with the next frame.
+    /// * [`Error::Truncated`](crate::Error::Truncated): The followed file was
+    ///   truncated. The file has been reopened, and reading can continue
+    ///   from its start.
+    /// * [`Error::Rotated`](crate::Error::Rotated): The followed file was
+    ///   replaced. The new file has been opened, and reading can continue
+    ///   from its start.
     // these unwraps aren't reachable
     #[allow(clippy::missing_panics_doc)]
     pub async fn read_frame(&mut self) -> crate::Result<crate::Frame> {
+        #[cfg(feature = "follow")]
+        let mut waiting_since = None;
         loop {
-            if let Some(frame) = self.parser.next_frame() {
+            if let Some(frame) = self.parser.try_next_frame()? {
                 return Ok(frame);
             }
             let bytes = self
@@ -37,8 +65,20 @@ impl<T: tokio::io::AsyncRead + std::marker::Unpin + Send> Reader<T> {
                 .await
                 .map_err(|source| crate::Error::Read { source })?;
             if bytes == 0 {
+                #[cfg(feature = "follow")]
+                if let Some(follow) = self.follow {
+                    self.wait(follow, &mut waiting_since).await?;
+                    continue;
+                }
                 return Err(crate::Error::EOF);
             }
+            #[cfg(feature = "follow")]
+            {
+                waiting_since = None;
+                if let Some((watch, _)) = &mut self.watch {
+                    watch.advance(&self.buf[..bytes]);
+                }
+            }
             self.parser.add_bytes(
                 // read() returning a value means that that many bytes are
                 // guaranteed to be available
@@ -53,4 +93,207 @@ impl<T: tokio::io::AsyncRead + std::marker::Unpin + Send> Reader<T> {
     pub fn offset(&self) -> Option<std::time::Duration> {
         self.parser.offset()
     }
+
+    /// Sets whether frames written with an extended header should be
+    /// recognized. Defaults to `false`.
+    ///
+    /// See
+    /// [`Parser::set_extended_header`](crate::Parser::set_extended_header).
+    pub fn set_extended_header(&mut self, extended: bool) {
+        self.parser.set_extended_header(extended);
+    }
+
+    /// Enables or disables follow mode.
+    ///
+    /// In follow mode, reaching the end of the input stream causes
+    /// [`read_frame`](Self::read_frame) to poll for more data according to
+    /// the given options, rather than returning
+    /// [`Error::EOF`](crate::Error::EOF) immediately. Note that this only
+    /// makes sense for input streams which can return more data after
+    /// indicating the end of the stream, such as files.
+    #[cfg(feature = "follow")]
+    pub fn set_follow(&mut self, options: Option<crate::FollowOptions>) {
+        self.follow = options;
+    }
+
+    #[cfg(feature = "follow")]
+    async fn wait(
+        &mut self,
+        follow: crate::FollowOptions,
+        waiting_since: &mut Option<tokio::time::Instant>,
+    ) -> crate::Result<()> {
+        self.check_watch().await?;
+        let waiting_since =
+            *waiting_since.get_or_insert_with(tokio::time::Instant::now);
+        if follow
+            .timeout
+            .is_some_and(|timeout| waiting_since.elapsed() >= timeout)
+        {
+            return Err(crate::Error::EOF);
+        }
+        tokio::time::sleep(follow.poll_interval).await;
+        Ok(())
+    }
+
+    #[cfg(feature = "follow")]
+    async fn check_watch(&mut self) -> crate::Result<()> {
+        let Some((watch, open)) = &mut self.watch else {
+            return Ok(());
+        };
+        let metadata = match tokio::fs::metadata(watch.path()).await {
+            Ok(metadata) => metadata,
+            // the file may have been moved away without having been
+            // replaced yet
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(())
+            }
+            Err(source) => return Err(crate::Error::Read { source }),
+        };
+        let mut res = watch.check(&metadata);
+        if res.is_ok() {
+            let (offset, len) = watch.tail();
+            res =
+                watch.check_tail(read_tail(watch.path(), offset, len).await);
+        }
+        if let Err(e) = res {
+            let file = tokio::fs::File::open(watch.path())
+                .await
+                .map_err(|source| crate::Error::Read { source })?;
+            let metadata = file
+                .metadata()
+                .await
+                .map_err(|source| crate::Error::Read { source })?;
+            watch.reset(&metadata);
+            self.input = open(file);
+            self.parser = crate::Parser::new();
+            return Err(e);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "follow")]
+async fn read_tail(
+    path: &std::path::Path,
+    offset: u64,
+    len: usize,
+) -> std::io::Result<Vec<u8>> {
+    use tokio::io::AsyncSeekExt as _;
+
+    let mut file = tokio::fs::File::open(path).await?;
+    file.seek(std::io::SeekFrom::Start(offset)).await?;
+    let mut tail = vec![0; len];
+    file.read_exact(&mut tail).await?;
+    Ok(tail)
+}
+
+#[cfg(feature = "follow")]
+impl Reader<tokio::fs::File> {
+    /// Opens the file at the given path and creates a new
+    /// [`Reader`](Self) for it in follow mode (see
+    /// [`set_follow`](Self::set_follow)).
+    ///
+    /// In addition to waiting for more data, this will also detect when the
+    /// file at the given path is truncated or replaced (for instance, by log
+    /// rotation), and reopen it.
+    ///
+    /// # Errors
+    /// * [`Error::Read`](crate::Error::Read): There was an error opening the
+    ///   file.
+    pub async fn follow_path(
+        path: impl AsRef<std::path::Path>,
+        options: crate::FollowOptions,
+    ) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let file = tokio::fs::File::open(path)
+            .await
+            .map_err(|source| crate::Error::Read { source })?;
+        let metadata = file
+            .metadata()
+            .await
+            .map_err(|source| crate::Error::Read { source })?;
+        let mut reader = Self::new(file);
+        reader.follow = Some(options);
+        reader.watch =
+            Some((crate::follow::Watch::new(path, &metadata), |file| file));
+        Ok(reader)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    #[cfg(feature = "follow")]
+    #[tokio::test]
+    async fn test_follow() {
+        use std::io::Write as _;
+
+        let path = std::env::temp_dir()
+            .join(format!("ttyrec-async-follow-{}", std::process::id()));
+        let mut fh = std::fs::File::create(&path).unwrap();
+        let mut creator = crate::Creator::new();
+        let mut write = |fh: &mut std::fs::File, data: &[u8]| {
+            let frame = creator.frame_after(std::time::Duration::ZERO, data);
+            let bytes: Vec<u8> = frame.try_into().unwrap();
+            fh.write_all(&bytes).unwrap();
+        };
+
+        write(&mut fh, b"foo");
+        let mut reader = crate::Reader::follow_path(
+            &path,
+            crate::FollowOptions {
+                poll_interval: std::time::Duration::from_millis(10),
+                timeout: Some(std::time::Duration::from_millis(50)),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(reader.read_frame().await.unwrap().data, b"foo");
+        assert!(matches!(reader.read_frame().await, Err(crate::Error::EOF)));
+
+        write(&mut fh, b"bar");
+        assert_eq!(reader.read_frame().await.unwrap().data, b"bar");
+
+        let mut fh = std::fs::File::create(&path).unwrap();
+        write(&mut fh, b"baz");
+        assert!(matches!(
+            reader.read_frame().await,
+            Err(crate::Error::Truncated)
+        ));
+        assert_eq!(reader.read_frame().await.unwrap().data, b"baz");
+
+        // truncated and then written past the previous read position
+        let mut fh = std::fs::File::create(&path).unwrap();
+        write(&mut fh, b"regrown");
+        assert!(matches!(
+            reader.read_frame().await,
+            Err(crate::Error::Truncated)
+        ));
+        assert_eq!(reader.read_frame().await.unwrap().data, b"regrown");
+
+        std::fs::remove_file(&path).unwrap();
+        let mut fh = std::fs::File::create(&path).unwrap();
+        write(&mut fh, b"quux");
+        if cfg!(unix) {
+            assert!(matches!(
+                reader.read_frame().await,
+                Err(crate::Error::Rotated)
+            ));
+        }
+        assert_eq!(reader.read_frame().await.unwrap().data, b"quux");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_eof() {
+        let frame = crate::Frame {
+            time: std::time::Duration::ZERO,
+            data: b"foo".to_vec(),
+        };
+        let bytes: Vec<u8> = frame.try_into().unwrap();
+        let mut reader = crate::Reader::new(&bytes[..]);
+        assert_eq!(reader.read_frame().await.unwrap().data, b"foo");
+        assert!(matches!(reader.read_frame().await, Err(crate::Error::EOF)));
+    }
 }
@@ -0,0 +1,113 @@
+/// Rebases the timestamps of a stream of frames.
+///
+/// Ttyrec files may contain either timestamps relative to the start of the
+/// recording or absolute timestamps (see [`Parser::offset`]). This struct
+/// shifts every frame in a stream by the same amount, such that the first
+/// frame it sees ends up at a chosen base time (either zero, or a given wall
+/// clock time) while the differences between frames are preserved.
+///
+/// [`Parser::offset`]: crate::Parser::offset
+#[derive(Debug, Clone, Default)]
+pub struct Rebaser {
+    base: std::time::Duration,
+    offset: Option<std::time::Duration>,
+}
+
+impl Rebaser {
+    /// Creates a new [`Rebaser`] which moves the first frame to time zero.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new [`Rebaser`] which moves the first frame to the given
+    /// wall clock time, producing absolute timestamps.
+    #[must_use]
+    pub fn with_start_time(start_time: std::time::SystemTime) -> Self {
+        Self {
+            base: start_time
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default(),
+            offset: None,
+        }
+    }
+
+    /// Returns the given frame with its timestamp rebased.
+    ///
+    /// Frames are expected to be passed in stream order. Frames with a
+    /// timestamp earlier than the first frame keep their distance from it,
+    /// and so end up earlier than the base time (or at zero, if they would
+    /// otherwise be negative).
+    pub fn rebase(
+        &mut self,
+        frame: crate::frame::Frame,
+    ) -> crate::frame::Frame {
+        let offset = *self.offset.get_or_insert(frame.time);
+        let time = frame.time.checked_sub(offset).map_or_else(
+            || self.base.saturating_sub(offset.saturating_sub(frame.time)),
+            |since_start| self.base + since_start,
+        );
+        crate::frame::Frame {
+            time,
+            data: frame.data,
+        }
+    }
+
+    /// The timestamp of the first frame seen, before rebasing.
+    ///
+    /// Returns [`None`] if no frames have been rebased yet.
+    #[must_use]
+    pub fn offset(&self) -> Option<std::time::Duration> {
+        self.offset
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_basic() {
+        let frames: Vec<_> =
+            [(1_600_000_000, 0), (1_600_000_038, 123_456_000)]
+                .into_iter()
+                .map(|(secs, nanos)| crate::frame::Frame {
+                    time: std::time::Duration::new(secs, nanos),
+                    data: b"foo".to_vec(),
+                })
+                .collect();
+
+        let mut rebaser = Rebaser::new();
+        let times: Vec<_> = frames
+            .iter()
+            .cloned()
+            .map(|frame| rebaser.rebase(frame).time)
+            .collect();
+        assert_eq!(
+            times,
+            vec![
+                std::time::Duration::new(0, 0),
+                std::time::Duration::new(38, 123_456_000),
+            ]
+        );
+        assert_eq!(
+            rebaser.offset(),
+            Some(std::time::Duration::new(1_600_000_000, 0))
+        );
+
+        let mut rebaser = Rebaser::with_start_time(
+            std::time::UNIX_EPOCH + std::time::Duration::new(1_000, 0),
+        );
+        let times: Vec<_> = frames
+            .into_iter()
+            .map(|frame| rebaser.rebase(frame).time)
+            .collect();
+        assert_eq!(
+            times,
+            vec![
+                std::time::Duration::new(1_000, 0),
+                std::time::Duration::new(1_038, 123_456_000),
+            ]
+        );
+    }
+}
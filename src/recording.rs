@@ -0,0 +1,152 @@
+/// Builds a synthetic recording with exact timestamps.
+///
+/// Unlike [`Creator`](crate::Creator), which timestamps frames using the
+/// current time, the builder keeps its own clock, which starts at zero and
+/// only moves when told to (with [`at`](Self::at) or [`gap`](Self::gap)).
+/// This makes it easy to construct deterministic fixtures for tests,
+/// including ones with specific timings or edge-case timestamps.
+///
+/// ```
+/// let frames = ttyrec::RecordingBuilder::new()
+///     .write(b"$ ")
+///     .gap(std::time::Duration::from_millis(500))
+///     .write(b"ls\r\n")
+///     .at(std::time::Duration::from_secs(2))
+///     .write(b"foo\r\n")
+///     .build();
+/// assert_eq!(frames.len(), 3);
+/// assert_eq!(frames[1].time, std::time::Duration::from_millis(500));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RecordingBuilder {
+    time: std::time::Duration,
+    frames: Vec<crate::frame::Frame>,
+}
+
+impl RecordingBuilder {
+    /// Creates a new, empty [`RecordingBuilder`], with its clock at zero.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the clock to the given time. Times are not required to be
+    /// increasing, so this can also be used to construct recordings with
+    /// timestamps which go backwards.
+    #[must_use]
+    pub fn at(mut self, time: std::time::Duration) -> Self {
+        self.time = time;
+        self
+    }
+
+    /// Advances the clock by the given amount.
+    #[must_use]
+    pub fn gap(mut self, gap: std::time::Duration) -> Self {
+        self.time = self.time.saturating_add(gap);
+        self
+    }
+
+    /// Adds a frame containing the given data, at the current time.
+    #[must_use]
+    pub fn write(mut self, data: impl AsRef<[u8]>) -> Self {
+        self.frames.push(crate::frame::Frame {
+            time: self.time,
+            data: data.as_ref().to_vec(),
+        });
+        self
+    }
+
+    /// Adds an [`ExtendedFrame`](crate::ExtendedFrame) of the given kind, at
+    /// the current time.
+    #[must_use]
+    pub fn extended(mut self, kind: crate::FrameKind) -> Self {
+        self.frames.push(
+            crate::ExtendedFrame {
+                time: self.time,
+                kind,
+            }
+            .into(),
+        );
+        self
+    }
+
+    /// Returns the frames added so far.
+    #[must_use]
+    pub fn frames(&self) -> &[crate::frame::Frame] {
+        &self.frames
+    }
+
+    /// Returns the frames which were added.
+    #[must_use]
+    pub fn build(self) -> Vec<crate::frame::Frame> {
+        self.frames
+    }
+
+    /// Serializes the frames which were added as ttyrec data.
+    ///
+    /// # Errors
+    /// * [`Error::FrameTooBig`](crate::Error::FrameTooBig): A frame
+    ///   contains too much data to be represented in a ttyrec file.
+    /// * [`Error::FrameTooLong`](crate::Error::FrameTooLong): A frame
+    ///   timestamp is too large to be represented in a ttyrec file.
+    pub fn to_bytes(&self) -> crate::Result<Vec<u8>> {
+        let encoder = crate::Encoder::new();
+        let mut bytes = Vec::with_capacity(
+            self.frames
+                .iter()
+                .map(crate::frame::Frame::encoded_len)
+                .sum(),
+        );
+        for frame in &self.frames {
+            encoder.encode(frame, &mut bytes)?;
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_basic() {
+        let builder = RecordingBuilder::new()
+            .at(std::time::Duration::new(38, 123_456_000))
+            .write(b"\x1b[2Jfoobar")
+            .gap(std::time::Duration::from_micros(1))
+            .extended(crate::FrameKind::Resize { rows: 24, cols: 80 })
+            .at(std::time::Duration::from_secs(1))
+            .write(vec![b'x'; 100_000]);
+        assert_eq!(
+            builder.frames()[1].time,
+            std::time::Duration::new(38, 123_457_000)
+        );
+        assert_eq!(
+            builder.frames()[2].time,
+            std::time::Duration::from_secs(1)
+        );
+
+        let bytes = builder.to_bytes().unwrap();
+        assert_eq!(
+            bytes[..22],
+            [
+                38, 0, 0, 0, 64, 226, 1, 0, 10, 0, 0, 0, 27, 91, 50, 74, 102,
+                111, 111, 98, 97, 114,
+            ]
+        );
+        let mut parser = crate::Parser::new();
+        parser.add_bytes(&bytes);
+        let frames: Vec<_> =
+            std::iter::from_fn(|| parser.next_frame()).collect();
+        assert_eq!(frames, builder.build());
+
+        let long = RecordingBuilder::new()
+            .at(std::time::Duration::from_secs(u64::from(u32::MAX) + 1))
+            .write(b"");
+        assert!(matches!(
+            long.to_bytes(),
+            Err(crate::Error::FrameTooLong { .. })
+        ));
+    }
+}
@@ -0,0 +1,161 @@
+// an RGB image, three bytes per pixel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    fn new(width: usize, height: usize, color: [u8; 3]) -> Self {
+        Self {
+            width,
+            height,
+            pixels: color.repeat(width * height),
+        }
+    }
+
+    fn set(&mut self, x: usize, y: usize, color: [u8; 3]) {
+        let i = (y * self.width + x) * 3;
+        self.pixels[i..i + 3].copy_from_slice(&color);
+    }
+
+    fn fill(
+        &mut self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        color: [u8; 3],
+    ) {
+        for y in y..y + height {
+            for x in x..x + width {
+                self.set(x, y, color);
+            }
+        }
+    }
+
+    // scales the image to the given size using nearest neighbor sampling
+    #[cfg(feature = "png")]
+    pub fn resize(&self, width: usize, height: usize) -> Self {
+        let mut pixels = Vec::with_capacity(width * height * 3);
+        for y in 0..height {
+            let sy = y * self.height / height;
+            for x in 0..width {
+                let sx = x * self.width / width;
+                let i = (sy * self.width + sx) * 3;
+                pixels.extend_from_slice(&self.pixels[i..i + 3]);
+            }
+        }
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+// renders the screen into an image of the given number of rows and columns
+// (which may differ from the size of the screen itself, if it was resized),
+// with each pixel of the font drawn as a `scale` by `scale` square
+pub fn render(
+    screen: &vt100::Screen,
+    palette: &crate::Palette,
+    rows: u16,
+    cols: u16,
+    scale: usize,
+) -> Image {
+    let cell_width = crate::font::WIDTH * scale;
+    let cell_height = crate::font::HEIGHT * scale;
+    let mut image = Image::new(
+        usize::from(cols) * cell_width,
+        usize::from(rows) * cell_height,
+        palette.background(),
+    );
+    let cursor = (!screen.hide_cursor()).then(|| screen.cursor_position());
+
+    for row in 0..rows {
+        for col in 0..cols {
+            let Some(cell) = screen.cell(row, col) else {
+                continue;
+            };
+            let x = usize::from(col) * cell_width;
+            let y = usize::from(row) * cell_height;
+
+            let mut fgcolor = cell.fgcolor();
+            if let vt100::Color::Idx(index @ 0..=7) = fgcolor {
+                if cell.bold() {
+                    fgcolor = vt100::Color::Idx(index + 8);
+                }
+            }
+            let mut fg = palette.resolve(fgcolor, palette.foreground());
+            let mut bg =
+                palette.resolve(cell.bgcolor(), palette.background());
+            if cell.inverse() != (cursor == Some((row, col))) {
+                std::mem::swap(&mut fg, &mut bg);
+            }
+            image.fill(x, y, cell_width, cell_height, bg);
+
+            let Some(c) = cell.contents().chars().next() else {
+                continue;
+            };
+            let mut glyph = crate::font::glyph(c);
+            if cell.underline() {
+                glyph[crate::font::UNDERLINE] = 0b11_1111;
+            }
+            for (gy, bits) in glyph.iter().enumerate() {
+                for gx in 0..crate::font::WIDTH {
+                    if bits & (1 << (crate::font::WIDTH - 1 - gx)) != 0 {
+                        image.fill(
+                            x + gx * scale,
+                            y + gy * scale,
+                            scale,
+                            scale,
+                            fg,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    image
+}
+
+pub fn too_large() -> crate::Error {
+    crate::Error::Encode {
+        source: "image dimensions are too large".into(),
+    }
+}
+
+pub fn encode_error<E: std::error::Error + Send + Sync + 'static>(
+    e: E,
+) -> crate::Error {
+    crate::Error::Encode {
+        source: Box::new(e),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_basic() {
+        let palette = crate::Palette::new();
+        let mut parser = vt100::Parser::new(2, 3, 0);
+        parser.process(b"\x1b[?25l\x1b[41m \x1b[m\x1b[2;3H\x1b[32m_");
+        let image = render(parser.screen(), &palette, 2, 3, 2);
+        assert_eq!(image.width, 36);
+        assert_eq!(image.height, 40);
+        let pixel = |x: usize, y: usize| {
+            let i = (y * image.width + x) * 3;
+            [image.pixels[i], image.pixels[i + 1], image.pixels[i + 2]]
+        };
+        assert_eq!(pixel(0, 0), palette.color(1));
+        assert_eq!(pixel(12, 0), palette.background());
+        // the bottom row of the glyph is at row 8 of the cell
+        assert_eq!(pixel(24, 20 + 16), palette.color(2));
+        assert_eq!(pixel(24, 20 + 14), palette.background());
+    }
+}
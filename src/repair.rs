@@ -0,0 +1,225 @@
+/// How a [`Repairer`] fixes frames whose timestamps go backwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RepairStrategy {
+    /// Frames are moved forward to the timestamp of the previous frame.
+    #[default]
+    Clamp,
+
+    /// Frames are held until a frame with a valid timestamp is seen, and
+    /// then spread evenly over the time between the last valid frame and
+    /// that frame. To bound memory use, if too many frames are held (see
+    /// [`Repairer::set_max_held`]), they are clamped instead.
+    Redistribute,
+}
+
+// the default number of frames held by RepairStrategy::Redistribute
+const DEFAULT_MAX_HELD: usize = 1024;
+
+/// Repairs the timestamps in a stream of frames.
+///
+/// Designed to be used in a streaming fashion, similar to
+/// [`Parser`](crate::Parser): add frames in stream order with
+/// [`add_frame`](Self::add_frame), and then call
+/// [`next_frame`](Self::next_frame) to retrieve repaired frames as they
+/// become available. Once the stream has ended, call
+/// [`finish`](Self::finish) to release any frames which are still being
+/// held.
+///
+/// The resulting stream will always have non-decreasing timestamps. If a
+/// maximum gap is configured via [`set_max_gap`](Self::set_max_gap), any
+/// longer gaps between frames are shortened to that length by shifting all
+/// later frames earlier.
+#[derive(Debug, Clone)]
+pub struct Repairer {
+    strategy: RepairStrategy,
+    max_gap: Option<std::time::Duration>,
+    max_held: usize,
+    shift: std::time::Duration,
+    last_input: Option<std::time::Duration>,
+    last: Option<std::time::Duration>,
+    held: Vec<crate::frame::Frame>,
+    ready: std::collections::VecDeque<crate::frame::Frame>,
+}
+
+impl Default for Repairer {
+    fn default() -> Self {
+        Self {
+            strategy: RepairStrategy::default(),
+            max_gap: None,
+            max_held: DEFAULT_MAX_HELD,
+            shift: std::time::Duration::ZERO,
+            last_input: None,
+            last: None,
+            held: vec![],
+            ready: std::collections::VecDeque::new(),
+        }
+    }
+}
+
+impl Repairer {
+    /// Creates a new [`Repairer`] using the given strategy.
+    #[must_use]
+    pub fn new(strategy: RepairStrategy) -> Self {
+        Self {
+            strategy,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the longest gap between consecutive frames which should be
+    /// preserved. Defaults to [`None`], which leaves gaps unchanged.
+    pub fn set_max_gap(&mut self, max_gap: Option<std::time::Duration>) {
+        self.max_gap = max_gap;
+    }
+
+    /// Sets the maximum number of frames held by
+    /// [`RepairStrategy::Redistribute`] while waiting for a frame with a
+    /// valid timestamp. Once this many frames are held, they are released
+    /// clamped to the timestamp of the last valid frame. Defaults to 1024.
+    pub fn set_max_held(&mut self, max_held: usize) {
+        self.max_held = max_held;
+    }
+
+    /// Adds the next frame in the stream.
+    pub fn add_frame(&mut self, mut frame: crate::frame::Frame) {
+        // gaps are measured from the latest input time seen so far, so that
+        // a frame which goes backwards doesn't make the following gap look
+        // longer than it really is
+        let last_input = *self.last_input.get_or_insert(frame.time);
+        if let Some(gap) = frame.time.checked_sub(last_input) {
+            if let Some(max_gap) = self.max_gap {
+                self.shift += gap.saturating_sub(max_gap);
+            }
+            self.last_input = Some(frame.time);
+        }
+        frame.time = frame.time.saturating_sub(self.shift);
+
+        let Some(last) = self.last else {
+            self.push(frame);
+            return;
+        };
+        if frame.time < last {
+            match self.strategy {
+                RepairStrategy::Clamp => {
+                    frame.time = last;
+                    self.push(frame);
+                }
+                RepairStrategy::Redistribute => {
+                    self.held.push(frame);
+                    if self.held.len() >= self.max_held {
+                        self.release_held();
+                    }
+                }
+            }
+            return;
+        }
+
+        if !self.held.is_empty() {
+            let span = frame.time.saturating_sub(last);
+            let count =
+                u32::try_from(self.held.len() + 1).unwrap_or(u32::MAX);
+            let held = std::mem::take(&mut self.held);
+            for (i, mut held_frame) in (1..).zip(held) {
+                held_frame.time = last + span * i / count;
+                self.ready.push_back(held_frame);
+            }
+        }
+        self.push(frame);
+    }
+
+    /// Returns the next repaired frame, if one is available.
+    pub fn next_frame(&mut self) -> Option<crate::frame::Frame> {
+        self.ready.pop_front()
+    }
+
+    /// Signals that the stream has ended, releasing any held frames. Held
+    /// frames are clamped to the timestamp of the last valid frame, since
+    /// there is no later frame to redistribute them against.
+    pub fn finish(&mut self) {
+        self.release_held();
+    }
+
+    fn release_held(&mut self) {
+        let last = self.last.unwrap_or_default();
+        for mut frame in std::mem::take(&mut self.held) {
+            frame.time = last;
+            self.ready.push_back(frame);
+        }
+    }
+
+    fn push(&mut self, frame: crate::frame::Frame) {
+        self.last = Some(frame.time);
+        self.ready.push_back(frame);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn repair(
+        repairer: &mut Repairer,
+        times: &[u64],
+    ) -> Vec<std::time::Duration> {
+        for (i, secs) in times.iter().enumerate() {
+            repairer.add_frame(crate::frame::Frame {
+                time: std::time::Duration::from_secs(*secs),
+                data: i.to_string().into_bytes(),
+            });
+        }
+        repairer.finish();
+        std::iter::from_fn(|| repairer.next_frame())
+            .map(|frame| frame.time)
+            .collect()
+    }
+
+    #[test]
+    fn test_basic() {
+        let secs = |secs: &[u64]| -> Vec<_> {
+            secs.iter()
+                .copied()
+                .map(std::time::Duration::from_secs)
+                .collect()
+        };
+
+        let mut repairer = Repairer::new(RepairStrategy::Clamp);
+        assert_eq!(
+            repair(&mut repairer, &[0, 10, 2, 4, 12]),
+            secs(&[0, 10, 10, 10, 12])
+        );
+
+        let mut repairer = Repairer::new(RepairStrategy::Redistribute);
+        assert_eq!(
+            repair(&mut repairer, &[0, 10, 2, 4, 13]),
+            secs(&[0, 10, 11, 12, 13])
+        );
+
+        let mut repairer = Repairer::new(RepairStrategy::Redistribute);
+        assert_eq!(
+            repair(&mut repairer, &[0, 10, 2, 4]),
+            secs(&[0, 10, 10, 10])
+        );
+
+        let mut repairer = Repairer::new(RepairStrategy::Clamp);
+        repairer.set_max_gap(Some(std::time::Duration::from_secs(5)));
+        assert_eq!(
+            repair(&mut repairer, &[0, 1, 3601, 3602]),
+            secs(&[0, 1, 6, 7])
+        );
+
+        let mut repairer = Repairer::new(RepairStrategy::Clamp);
+        repairer.set_max_gap(Some(std::time::Duration::from_secs(5)));
+        assert_eq!(
+            repair(&mut repairer, &[0, 100, 2, 103]),
+            secs(&[0, 5, 5, 8])
+        );
+
+        // only a limited number of frames are held
+        let mut repairer = Repairer::new(RepairStrategy::Redistribute);
+        repairer.set_max_held(2);
+        assert_eq!(
+            repair(&mut repairer, &[0, 10, 2, 3, 4, 14]),
+            secs(&[0, 10, 10, 10, 12, 14])
+        );
+    }
+}
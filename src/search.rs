@@ -0,0 +1,354 @@
+/// A single match found by a [`Searcher`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hit {
+    /// Index of the frame (in the input stream) in which the match started.
+    pub index: usize,
+
+    /// Byte offset within the (unstripped) frame data at which the match
+    /// started.
+    pub offset: usize,
+
+    /// Timestamp of the frame in which the match started.
+    pub time: std::time::Duration,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Origin {
+    index: usize,
+    offset: usize,
+    time: std::time::Duration,
+}
+
+/// Searches the text of a stream of frames for a string.
+///
+/// Terminal escape sequences (including
+/// [`ExtendedFrame`](crate::ExtendedFrame) sequences) and control
+/// characters other than newlines and tabs are removed before searching,
+/// and the text is searched as a single continuous stream, so matches
+/// which are split across frames (or which are interrupted by escape
+/// sequences) are still found.
+///
+/// Designed to be used in a streaming fashion, similar to
+/// [`Parser`](crate::Parser): add frames in stream order with
+/// [`add_frame`](Self::add_frame), and then call
+/// [`next_hit`](Self::next_hit) to retrieve any matches which have been
+/// found.
+///
+/// Note that this searches the raw output stream, so text which was
+/// edited on screen (for instance, with backspace) will not match the way
+/// it was displayed. With the `"screen"` feature enabled,
+/// `ScreenSearcher` can be used to search the rendered screen contents
+/// instead.
+pub struct Searcher {
+    needle: Vec<u8>,
+    case_insensitive: bool,
+    stripper: crate::strip::Stripper,
+    window: std::collections::VecDeque<(u8, Origin)>,
+    hits: std::collections::VecDeque<Hit>,
+    index: usize,
+}
+
+impl Searcher {
+    /// Creates a new [`Searcher`] which searches for the given string.
+    #[must_use]
+    pub fn new(needle: &str) -> Self {
+        Self {
+            needle: needle.as_bytes().to_vec(),
+            case_insensitive: false,
+            stripper: crate::strip::Stripper::new(),
+            window: std::collections::VecDeque::new(),
+            hits: std::collections::VecDeque::new(),
+            index: 0,
+        }
+    }
+
+    /// Sets whether ASCII letters should be matched case insensitively.
+    /// Defaults to false.
+    pub fn set_case_insensitive(&mut self, case_insensitive: bool) {
+        self.case_insensitive = case_insensitive;
+    }
+
+    /// Adds the next frame in the stream.
+    pub fn add_frame(&mut self, frame: &crate::frame::Frame) {
+        let index = self.index;
+        self.index += 1;
+        if self.needle.is_empty() {
+            return;
+        }
+
+        for (offset, &c) in frame.data.iter().enumerate() {
+            if !self.stripper.keep(c) || !is_text(c) {
+                continue;
+            }
+            if self.window.len() == self.needle.len() {
+                self.window.pop_front();
+            }
+            self.window.push_back((
+                c,
+                Origin {
+                    index,
+                    offset,
+                    time: frame.time,
+                },
+            ));
+            if self.window.len() == self.needle.len() && self.matches() {
+                let origin = self.window[0].1;
+                self.hits.push_back(Hit {
+                    index: origin.index,
+                    offset: origin.offset,
+                    time: origin.time,
+                });
+            }
+        }
+    }
+
+    /// Returns the next match, if one has been found.
+    pub fn next_hit(&mut self) -> Option<Hit> {
+        self.hits.pop_front()
+    }
+
+    fn matches(&self) -> bool {
+        self.window.iter().zip(&self.needle).all(|(&(c, _), &n)| {
+            if self.case_insensitive {
+                c.eq_ignore_ascii_case(&n)
+            } else {
+                c == n
+            }
+        })
+    }
+}
+
+/// A single match found by a `ScreenSearcher`.
+#[cfg(feature = "screen")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScreenHit {
+    /// Index of the frame (in the input stream) after which the match first
+    /// appeared on the screen.
+    pub index: usize,
+
+    /// Timestamp of the frame after which the match first appeared on the
+    /// screen.
+    pub time: std::time::Duration,
+
+    /// Screen row at which the match starts.
+    pub row: u16,
+
+    /// Screen column at which the match starts.
+    pub col: u16,
+}
+
+/// Searches the rendered screen contents of a stream of frames for a
+/// string.
+///
+/// Each frame is run through a terminal emulator, and the screen is
+/// searched after each frame. A match is reported once when it first
+/// appears at a given position on the screen, rather than for every frame
+/// during which it remains visible. Matches must lie within a single row
+/// of the screen.
+///
+/// Terminal resizes recorded as [`ExtendedFrame`](crate::ExtendedFrame)s
+/// are applied to the emulated terminal.
+#[cfg(feature = "screen")]
+pub struct ScreenSearcher {
+    needle: String,
+    case_insensitive: bool,
+    screen: vt100::Parser,
+    visible: std::collections::HashSet<(u16, u16)>,
+    hits: std::collections::VecDeque<ScreenHit>,
+    index: usize,
+}
+
+#[cfg(feature = "screen")]
+impl ScreenSearcher {
+    /// Creates a new [`ScreenSearcher`] which searches for the given string
+    /// on a terminal of the given size.
+    #[must_use]
+    pub fn new(needle: &str, rows: u16, cols: u16) -> Self {
+        Self {
+            needle: needle.to_string(),
+            case_insensitive: false,
+            screen: vt100::Parser::new(rows, cols, 0),
+            visible: std::collections::HashSet::new(),
+            hits: std::collections::VecDeque::new(),
+            index: 0,
+        }
+    }
+
+    /// Sets whether ASCII letters should be matched case insensitively.
+    /// Defaults to false.
+    pub fn set_case_insensitive(&mut self, case_insensitive: bool) {
+        self.case_insensitive = case_insensitive;
+    }
+
+    /// Changes the size of the emulated terminal.
+    pub fn set_size(&mut self, rows: u16, cols: u16) {
+        self.screen.screen_mut().set_size(rows, cols);
+    }
+
+    /// Adds the next frame in the stream.
+    pub fn add_frame(&mut self, frame: &crate::frame::Frame) {
+        let index = self.index;
+        self.index += 1;
+
+        if crate::extended::is_extension(&frame.data) {
+            if let crate::FrameKind::Resize { rows, cols } =
+                crate::ExtendedFrame::from(frame.clone()).kind
+            {
+                self.set_size(rows, cols);
+            }
+        }
+        self.screen.process(&frame.data);
+        if self.needle.is_empty() {
+            return;
+        }
+
+        let needle = if self.case_insensitive {
+            self.needle.to_ascii_lowercase()
+        } else {
+            self.needle.clone()
+        };
+        let screen = self.screen.screen();
+        let (rows, cols) = screen.size();
+        let mut visible = std::collections::HashSet::new();
+        for row in 0..rows {
+            let mut text = String::new();
+            let mut text_cols = vec![];
+            for col in 0..cols {
+                let Some(cell) = screen.cell(row, col) else {
+                    continue;
+                };
+                if cell.is_wide_continuation() {
+                    continue;
+                }
+                let contents = if cell.has_contents() {
+                    cell.contents()
+                } else {
+                    " "
+                };
+                text.push_str(contents);
+                text_cols.resize(text.len(), col);
+            }
+            if self.case_insensitive {
+                text.make_ascii_lowercase();
+            }
+            for (start, _) in text.match_indices(&needle) {
+                let pos = (row, text_cols[start]);
+                if !self.visible.contains(&pos) {
+                    self.hits.push_back(ScreenHit {
+                        index,
+                        time: frame.time,
+                        row: pos.0,
+                        col: pos.1,
+                    });
+                }
+                visible.insert(pos);
+            }
+        }
+        self.visible = visible;
+    }
+
+    /// Returns the next match, if one has been found.
+    pub fn next_hit(&mut self) -> Option<ScreenHit> {
+        self.hits.pop_front()
+    }
+}
+
+fn is_text(c: u8) -> bool {
+    c == b'\n' || c == b'\t' || (c >= 0x20 && c != 0x7f)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    fn search(searcher: &mut Searcher, chunks: &[&[u8]]) -> Vec<Hit> {
+        for (i, chunk) in chunks.iter().enumerate() {
+            searcher.add_frame(&crate::frame::Frame {
+                time: std::time::Duration::from_secs(
+                    u64::try_from(i).unwrap(),
+                ),
+                data: chunk.to_vec(),
+            });
+        }
+        std::iter::from_fn(|| searcher.next_hit()).collect()
+    }
+
+    #[test]
+    fn test_basic() {
+        let mut searcher = Searcher::new("rm -rf");
+        assert_eq!(
+            search(
+                &mut searcher,
+                &[
+                    b"$ rm -rf /tmp/foo\r\n",
+                    b"$ \x1b[1mrm\x1b[m -",
+                    b"r",
+                    b"f .\r\n",
+                    b"$ RM -RF\r\n",
+                ]
+            ),
+            vec![
+                Hit {
+                    index: 0,
+                    offset: 2,
+                    time: std::time::Duration::from_secs(0),
+                },
+                Hit {
+                    index: 1,
+                    offset: 6,
+                    time: std::time::Duration::from_secs(1),
+                },
+            ]
+        );
+
+        let mut searcher = Searcher::new("rm -rf");
+        searcher.set_case_insensitive(true);
+        assert_eq!(
+            search(&mut searcher, &[b"$ RM -RF\r\n"]),
+            vec![Hit {
+                index: 0,
+                offset: 2,
+                time: std::time::Duration::from_secs(0),
+            }]
+        );
+    }
+
+    #[cfg(feature = "screen")]
+    #[test]
+    fn test_screen() {
+        let mut searcher = ScreenSearcher::new("rm -rf", 24, 80);
+        for (i, chunk) in [
+            &b"$ rm -rx\x08f"[..],
+            b"\r\n$ ls",
+            b"\x1b[2J\x1b[Hfoo rm -rf",
+        ]
+        .iter()
+        .enumerate()
+        {
+            searcher.add_frame(&crate::frame::Frame {
+                time: std::time::Duration::from_secs(
+                    u64::try_from(i).unwrap(),
+                ),
+                data: chunk.to_vec(),
+            });
+        }
+        assert_eq!(
+            std::iter::from_fn(|| searcher.next_hit()).collect::<Vec<_>>(),
+            vec![
+                ScreenHit {
+                    index: 0,
+                    time: std::time::Duration::from_secs(0),
+                    row: 0,
+                    col: 2,
+                },
+                ScreenHit {
+                    index: 2,
+                    time: std::time::Duration::from_secs(2),
+                    row: 0,
+                    col: 4,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,207 @@
+/// Writes terminal output and user input to a pair of parallel ttyrec
+/// streams.
+///
+/// Output and input are each written to their own standard ttyrec stream
+/// (so the output stream can be played back by any ttyrec player), with
+/// the timestamps of both streams generated by the output stream's
+/// [`Creator`](crate::Creator). The two streams can be recombined into a
+/// single time-ordered sequence with [`SessionReader`].
+pub struct SessionWriter<O: tokio::io::AsyncWrite, I: tokio::io::AsyncWrite> {
+    output: crate::Writer<O>,
+    input: crate::Writer<I>,
+}
+
+impl<O, I> SessionWriter<O, I>
+where
+    O: tokio::io::AsyncWrite + std::marker::Unpin + Send,
+    I: tokio::io::AsyncWrite + std::marker::Unpin + Send,
+{
+    /// Creates a new [`SessionWriter`] from a pair of
+    /// [`tokio::io::AsyncWrite`] instances.
+    pub fn new(output: O, input: I) -> Self {
+        Self::with_creator(output, input, crate::Creator::new())
+    }
+
+    /// Creates a new [`SessionWriter`] which uses the given
+    /// [`Creator`](crate::Creator) to timestamp frames.
+    pub fn with_creator(
+        output: O,
+        input: I,
+        creator: crate::Creator,
+    ) -> Self {
+        Self {
+            output: crate::Writer::with_creator(output, creator),
+            input: crate::Writer::new(input),
+        }
+    }
+
+    /// Writes a new frame to the output stream, using the current time and
+    /// given data.
+    ///
+    /// # Errors
+    /// * [`Error::Write`](crate::Error::Write): There was an error writing to
+    ///   the output stream.
+    pub async fn output(&mut self, data: &[u8]) -> crate::Result<()> {
+        self.output_at(std::time::Instant::now(), data).await
+    }
+
+    /// Writes a new frame to the output stream, using the given time and
+    /// data.
+    ///
+    /// # Errors
+    /// * [`Error::Write`](crate::Error::Write): There was an error writing to
+    ///   the output stream.
+    pub async fn output_at(
+        &mut self,
+        cur_time: std::time::Instant,
+        data: &[u8],
+    ) -> crate::Result<()> {
+        self.output.frame_at(cur_time, data).await
+    }
+
+    /// Writes a new frame to the input stream, using the current time and
+    /// given data.
+    ///
+    /// # Errors
+    /// * [`Error::Write`](crate::Error::Write): There was an error writing to
+    ///   the input stream.
+    pub async fn input(&mut self, data: &[u8]) -> crate::Result<()> {
+        self.input_at(std::time::Instant::now(), data).await
+    }
+
+    /// Writes a new frame to the input stream, using the given time and
+    /// data.
+    ///
+    /// # Errors
+    /// * [`Error::Write`](crate::Error::Write): There was an error writing to
+    ///   the input stream.
+    pub async fn input_at(
+        &mut self,
+        cur_time: std::time::Instant,
+        data: &[u8],
+    ) -> crate::Result<()> {
+        let time = self.output.time_at(cur_time);
+        self.input.write_data(time, data).await
+    }
+
+    /// Flushes both the output and input streams.
+    ///
+    /// # Errors
+    /// * [`Error::Write`](crate::Error::Write): There was an error writing to
+    ///   either stream.
+    pub async fn flush(&mut self) -> crate::Result<()> {
+        self.output.flush().await?;
+        self.input.flush().await
+    }
+
+    /// Flushes both streams and returns the underlying output and input
+    /// streams.
+    ///
+    /// # Errors
+    /// * [`Error::Write`](crate::Error::Write): There was an error writing to
+    ///   either stream.
+    pub async fn into_inner(self) -> crate::Result<(O, I)> {
+        Ok((
+            self.output.into_inner().await?,
+            self.input.into_inner().await?,
+        ))
+    }
+}
+
+/// Reads a pair of output and input ttyrec streams (as written by
+/// [`SessionWriter`]) and merges them back into a single time-ordered
+/// sequence of [`ExtendedFrame`](crate::ExtendedFrame) values.
+///
+/// Frames from the output stream are returned as
+/// [`FrameKind::Output`](crate::FrameKind::Output) and frames from the input
+/// stream as [`FrameKind::Input`](crate::FrameKind::Input).
+pub struct SessionReader<T: tokio::io::AsyncRead> {
+    merger: crate::Merger<T>,
+}
+
+impl<T: tokio::io::AsyncRead + std::marker::Unpin + Send> SessionReader<T> {
+    /// Creates a new [`SessionReader`] from a pair of
+    /// [`tokio::io::AsyncRead`] instances.
+    pub fn new(output: T, input: T) -> Self {
+        Self {
+            merger: crate::Merger::new(vec![
+                crate::Reader::new(output),
+                crate::Reader::new(input),
+            ]),
+        }
+    }
+
+    /// Returns the next frame from either stream.
+    ///
+    /// # Errors
+    /// * [`Error::EOF`](crate::Error::EOF): Both input streams have been
+    ///   closed.
+    /// * [`Error::Read`](crate::Error::Read): There was an error reading from
+    ///   either input stream.
+    pub async fn read_frame(
+        &mut self,
+    ) -> crate::Result<crate::ExtendedFrame> {
+        let crate::MergedFrame { source, frame } =
+            self.merger.read_frame().await?;
+        let kind = if source == 0 {
+            crate::FrameKind::Output(frame.data)
+        } else {
+            crate::FrameKind::Input(frame.data)
+        };
+        Ok(crate::ExtendedFrame {
+            time: frame.time,
+            kind,
+        })
+    }
+
+    /// How much the timestamps in the merged stream should be offset by.
+    ///
+    /// See [`Merger::offset`](crate::Merger::offset).
+    #[must_use]
+    pub fn offset(&self) -> Option<std::time::Duration> {
+        self.merger.offset()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_basic() {
+        let base_time = std::time::Instant::now();
+        let at =
+            |millis| base_time + std::time::Duration::from_millis(millis);
+
+        let mut writer = SessionWriter::new(vec![], vec![]);
+        writer.output_at(at(0), b"$ ").await.unwrap();
+        writer.input_at(at(500), b"ls\r").await.unwrap();
+        writer
+            .output_at(at(510), b"ls\r\nfoo bar\r\n$ ")
+            .await
+            .unwrap();
+        writer.input_at(at(1500), b"\x04").await.unwrap();
+        let (output, input) = writer.into_inner().await.unwrap();
+
+        let mut reader = SessionReader::new(&output[..], &input[..]);
+        let mut frames = vec![];
+        while let Ok(frame) = reader.read_frame().await {
+            frames.push((frame.time.as_millis(), frame.kind));
+        }
+        assert_eq!(
+            frames,
+            vec![
+                (0, crate::FrameKind::Output(b"$ ".to_vec())),
+                (500, crate::FrameKind::Input(b"ls\r".to_vec())),
+                (
+                    510,
+                    crate::FrameKind::Output(b"ls\r\nfoo bar\r\n$ ".to_vec())
+                ),
+                (1500, crate::FrameKind::Input(b"\x04".to_vec())),
+            ]
+        );
+        assert_eq!(reader.offset(), Some(std::time::Duration::ZERO));
+        assert!(matches!(reader.read_frame().await, Err(crate::Error::EOF)));
+    }
+}
@@ -0,0 +1,39 @@
+//! [`proptest`](https://docs.rs/proptest) strategies for generating ttyrec
+//! data, for use in property-based tests.
+//!
+//! ```
+//! proptest::proptest!(|(frame in ttyrec::strategy::frame())| {
+//!     let len = frame.encoded_len();
+//!     let bytes = Vec::<u8>::try_from(frame).unwrap();
+//!     proptest::prop_assert_eq!(bytes.len(), len);
+//! });
+//! ```
+
+use proptest::strategy::Strategy as _;
+
+/// Generates a single [`Frame`](crate::Frame).
+///
+/// Generated frames can always be represented in a ttyrec file: the
+/// timestamp fits in 32 bits of seconds and has microsecond precision. The
+/// frame contains up to 1024 bytes of arbitrary data.
+pub fn frame(
+) -> impl proptest::strategy::Strategy<Value = crate::frame::Frame> {
+    (
+        proptest::num::u32::ANY,
+        0..1_000_000_u32,
+        proptest::collection::vec(proptest::num::u8::ANY, 0..1024),
+    )
+        .prop_map(|(secs, micros, data)| crate::frame::Frame {
+            time: std::time::Duration::new(u64::from(secs), micros * 1_000),
+            data,
+        })
+}
+
+/// Generates a sequence of frames (see [`frame`]) whose length is in the
+/// given range. Timestamps are not required to be increasing, since the
+/// format itself doesn't require it.
+pub fn frames(
+    len: impl Into<proptest::collection::SizeRange>,
+) -> impl proptest::strategy::Strategy<Value = Vec<crate::frame::Frame>> {
+    proptest::collection::vec(frame(), len)
+}
@@ -0,0 +1,101 @@
+// removes terminal escape sequences from a byte stream, one byte at a time,
+// so that sequences split across frames are still handled correctly
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum State {
+    #[default]
+    Ground,
+    Escape,
+    EscapeIntermediate,
+    Csi,
+    String,
+    StringEscape,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Stripper {
+    state: State,
+}
+
+impl Stripper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // returns whether the given byte is part of the text of the stream
+    // (which includes control characters other than ESC), as opposed to
+    // being part of an escape sequence
+    pub fn keep(&mut self, c: u8) -> bool {
+        match self.state {
+            State::Ground => {
+                if c != b'\x1b' {
+                    return true;
+                }
+                self.state = State::Escape;
+            }
+            State::Escape => self.escape(c),
+            State::EscapeIntermediate => {
+                if !(0x20..=0x2f).contains(&c) {
+                    self.state = State::Ground;
+                }
+            }
+            State::Csi => match c {
+                b'\x1b' => self.state = State::Escape,
+                // CAN and SUB abort the sequence
+                b'\x18' | b'\x1a' | 0x40..=0x7e => self.state = State::Ground,
+                _ => {}
+            },
+            State::String => match c {
+                b'\x07' => self.state = State::Ground,
+                b'\x1b' => self.state = State::StringEscape,
+                _ => {}
+            },
+            State::StringEscape => {
+                if c == b'\\' {
+                    self.state = State::Ground;
+                } else {
+                    self.escape(c);
+                }
+            }
+        }
+        false
+    }
+
+    // returns whether the stream is currently outside of any escape
+    // sequence
+    pub fn is_ground(&self) -> bool {
+        self.state == State::Ground
+    }
+
+    fn escape(&mut self, c: u8) {
+        self.state = match c {
+            b'\x1b' => State::Escape,
+            b'[' => State::Csi,
+            b']' | b'P' | b'X' | b'^' | b'_' => State::String,
+            0x20..=0x2f => State::EscapeIntermediate,
+            _ => State::Ground,
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn strip(chunks: &[&[u8]]) -> Vec<u8> {
+        let mut stripper = Stripper::new();
+        chunks
+            .iter()
+            .flat_map(|chunk| chunk.iter().copied())
+            .filter(|&c| stripper.keep(c))
+            .collect()
+    }
+
+    #[test]
+    fn test_basic() {
+        assert_eq!(strip(&[b"foo\x1b[31mbar\x1b[m\r\n"]), b"foobar\r\n");
+        assert_eq!(strip(&[b"foo\x1b[3", b"1mbar\x1b", b"[m"]), b"foobar");
+        assert_eq!(strip(&[b"\x1b]0;title\x07a\x1b]0;title\x1b\\b"]), b"ab");
+        assert_eq!(strip(&[b"\x1b(Ba\x1b=b\x1b7c"]), b"abc");
+        assert_eq!(strip(&[b"\x1b_ttyrec;input;ls\x1b\\"]), b"");
+    }
+}
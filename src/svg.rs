@@ -0,0 +1,234 @@
+use std::fmt::Write as _;
+
+const CELL_WIDTH: usize = 9;
+const CELL_HEIGHT: usize = 18;
+const FONT_SIZE: usize = 15;
+const BASELINE: usize = 14;
+
+/// Exports a recording as an animated SVG image.
+///
+/// Frames are run through a terminal emulator, and each distinct screen
+/// state is drawn as text, so the result stays sharp at any size. The
+/// screens are animated with CSS keyframes, and identical lines are only
+/// stored once no matter how many screens they appear in, which keeps the
+/// file small. As with [`Animation`](crate::Animation) (if enabled), long
+/// pauses can be shortened (see [`set_idle_limit`](Self::set_idle_limit))
+/// and playback sped up (see [`set_speed`](Self::set_speed)), and the last
+/// screen is shown for one second before the animation loops.
+///
+/// Add frames in stream order with [`add_frame`](Self::add_frame), and then
+/// generate the image with [`to_svg`](Self::to_svg).
+pub struct SvgExporter {
+    timeline: crate::timeline::Timeline,
+    rows: u16,
+    cols: u16,
+    palette: crate::Palette,
+}
+
+impl SvgExporter {
+    /// Creates a new [`SvgExporter`] for a terminal of the given size.
+    #[must_use]
+    pub fn new(rows: u16, cols: u16) -> Self {
+        Self {
+            timeline: crate::timeline::Timeline::new(rows, cols),
+            rows,
+            cols,
+            palette: crate::Palette::new(),
+        }
+    }
+
+    /// Sets the colors used to draw the screen.
+    pub fn set_palette(&mut self, palette: crate::Palette) {
+        self.palette = palette;
+    }
+
+    /// Sets the maximum amount of time which can pass between frames.
+    /// Longer pauses in the recording are shortened to this length. Passing
+    /// [`None`] (the default) disables this.
+    pub fn set_idle_limit(&mut self, limit: Option<std::time::Duration>) {
+        self.timeline.set_idle_limit(limit);
+    }
+
+    /// Sets the playback speed relative to the recording. For instance, 2.0
+    /// plays back at double speed. Defaults to 1.0. Only affects frames
+    /// added after it is called.
+    pub fn set_speed(&mut self, speed: f64) {
+        self.timeline.set_speed(speed);
+    }
+
+    /// Adds the next frame in the stream.
+    pub fn add_frame(&mut self, frame: &crate::frame::Frame) {
+        self.timeline.add_frame(frame);
+    }
+
+    /// Generates the SVG image containing all of the frames added so far.
+    #[must_use]
+    pub fn to_svg(&self) -> String {
+        let width = usize::from(self.cols) * CELL_WIDTH;
+        let height = usize::from(self.rows) * CELL_HEIGHT;
+
+        let mut lines: Vec<String> = vec![];
+        let mut line_ids = std::collections::HashMap::new();
+        let mut screens = String::new();
+        let mut starts = vec![];
+        let mut total = std::time::Duration::ZERO;
+        let mut count = 0;
+        for (parser, start, duration) in
+            self.timeline.screens(std::time::Duration::from_millis(1))
+        {
+            // writing to a String can't fail
+            let _ = write!(
+                screens,
+                "<g transform=\"translate({})\">",
+                count * width
+            );
+            for row in 0..self.rows {
+                let line = self.line(parser.screen(), row);
+                if line.is_empty() {
+                    continue;
+                }
+                let id = *line_ids.entry(line).or_insert_with_key(|line| {
+                    lines.push(line.clone());
+                    lines.len() - 1
+                });
+                let _ = write!(
+                    screens,
+                    "<use xlink:href=\"#l{id}\" y=\"{}\"/>",
+                    usize::from(row) * CELL_HEIGHT
+                );
+            }
+            screens.push_str("</g>\n");
+            starts.push(start);
+            total = start + duration;
+            count += 1;
+        }
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" \
+             xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+             width=\"{width}\" height=\"{height}\" \
+             viewBox=\"0 0 {width} {height}\">"
+        );
+        svg.push_str("<style>\n");
+        let _ = writeln!(
+            svg,
+            "text {{ font-family: monospace; font-size: {FONT_SIZE}px; \
+             white-space: pre; }}"
+        );
+        if count > 1 {
+            let _ = writeln!(
+                svg,
+                ".a {{ animation: k {:.3}s steps(1, end) infinite; }}",
+                total.as_secs_f64()
+            );
+            svg.push_str("@keyframes k {\n");
+            for (i, start) in starts.iter().enumerate() {
+                let _ = writeln!(
+                    svg,
+                    "{:.3}% {{ transform: translateX(-{}px); }}",
+                    start.as_secs_f64() / total.as_secs_f64() * 100.0,
+                    i * width
+                );
+            }
+            svg.push_str("}\n");
+        }
+        svg.push_str("</style>\n");
+        let _ = writeln!(
+            svg,
+            "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>",
+            crate::markup::hex(self.palette.background())
+        );
+        svg.push_str("<defs>\n");
+        for (id, line) in lines.iter().enumerate() {
+            let _ = writeln!(svg, "<g id=\"l{id}\">{line}</g>");
+        }
+        svg.push_str("</defs>\n");
+        svg.push_str("<g class=\"a\">\n");
+        svg.push_str(&screens);
+        svg.push_str("</g>\n</svg>\n");
+        svg
+    }
+
+    // returns the markup for a single row of the screen, positioned at the
+    // top of the image
+    fn line(&self, screen: &vt100::Screen, row: u16) -> String {
+        let runs = crate::markup::runs(screen, row, self.cols, &self.palette);
+        let mut line = String::new();
+        for (i, (col, attrs, text)) in runs.iter().enumerate() {
+            let x = col * CELL_WIDTH;
+            if attrs.bg != self.palette.background() {
+                let end = runs.get(i + 1).map_or_else(
+                    || usize::from(self.cols),
+                    |(col, _, _)| *col,
+                );
+                let _ = write!(
+                    line,
+                    "<rect x=\"{x}\" width=\"{}\" height=\"{CELL_HEIGHT}\" \
+                     fill=\"{}\"/>",
+                    (end - col) * CELL_WIDTH,
+                    crate::markup::hex(attrs.bg)
+                );
+            }
+            // trailing spaces don't need to be drawn, since the background
+            // is handled separately
+            let text = text.trim_end();
+            if text.is_empty() {
+                continue;
+            }
+            let _ = write!(
+                line,
+                "<text x=\"{x}\" y=\"{BASELINE}\" fill=\"{}\"",
+                crate::markup::hex(attrs.fg)
+            );
+            if attrs.bold {
+                line.push_str(" font-weight=\"bold\"");
+            }
+            if attrs.italic {
+                line.push_str(" font-style=\"italic\"");
+            }
+            if attrs.underline {
+                line.push_str(" text-decoration=\"underline\"");
+            }
+            line.push('>');
+            line.push_str(&crate::markup::escape(text));
+            line.push_str("</text>");
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_basic() {
+        let mut exporter = SvgExporter::new(3, 10);
+        for (millis, data) in [
+            (0, &b"\x1b[?25l$ "[..]),
+            (500, b"ls\r\n"),
+            (1000, b"a&b \x1b[41m \x1b[m\r\n$ "),
+        ] {
+            exporter.add_frame(&crate::frame::Frame {
+                time: std::time::Duration::from_millis(millis),
+                data: data.to_vec(),
+            });
+        }
+        let svg = exporter.to_svg();
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains("viewBox=\"0 0 90 54\""));
+        assert!(svg
+            .contains(".a { animation: k 2.000s steps(1, end) infinite; }"));
+        assert!(svg.contains("25.000% { transform: translateX(-90px); }"));
+        assert!(svg.contains("50.000% { transform: translateX(-180px); }"));
+        // "$ " is shown in all three screens, but only defined once
+        assert_eq!(svg.matches(">$</text>").count(), 1);
+        assert_eq!(svg.matches(">$ ls</text>").count(), 1);
+        assert!(svg.contains(">a&amp;b</text>"));
+        assert!(svg.contains(
+            "<rect x=\"36\" width=\"9\" height=\"18\" fill=\"#cd0000\"/>"
+        ));
+    }
+}
@@ -0,0 +1,353 @@
+//! Helpers for snapshot testing terminal programs against golden
+//! recordings.
+//!
+//! [`record`] runs a command under a pty and records its output, and
+//! [`Golden`] compares a recording against one stored on disk. Setting the
+//! `TTYREC_UPDATE` environment variable to `1` when running the tests
+//! writes the new recordings to disk instead of comparing them, similar to
+//! the update mode of snapshot testing libraries such as `insta`.
+//!
+//! This module is only available on Linux, since the details of how ptys
+//! report that the command has exited vary between platforms.
+//!
+//! ```no_run
+//! # fn main() {
+//! ttyrec::testing::Golden::new("tests/golden/ls.ttyrec")
+//!     .assert_command(std::process::Command::new("ls").arg("/"));
+//! # }
+//! ```
+
+use std::os::unix::process::CommandExt as _;
+
+const UPDATE_VAR: &str = "TTYREC_UPDATE";
+
+nix::ioctl_write_int_bad!(tiocsctty, nix::libc::TIOCSCTTY);
+
+/// Runs a command under a pty of the given size, returning everything it
+/// wrote to the terminal as ttyrec data.
+///
+/// The command's stdin, stdout, and stderr are all connected to the pty,
+/// and it becomes the session leader with the pty as its controlling
+/// terminal, so it sees the same environment it would in a real terminal
+/// emulator (other than environment variables such as `TERM`, which are
+/// passed through from the command as given). `input` is written to the
+/// terminal as soon as the command starts, while its output is being
+/// recorded, so commands which produce a lot of output before reading
+/// their input don't deadlock. Recording ends once every process holding the
+/// pty open has exited.
+///
+/// # Errors
+/// * [`Error::Spawn`](crate::Error::Spawn): There was an error creating
+///   the pty or starting the command.
+/// * [`Error::Read`](crate::Error::Read): There was an error waiting for
+///   or reading from the pty.
+/// * [`Error::Write`](crate::Error::Write): There was an error writing
+///   `input` to the pty.
+pub fn record(
+    command: &mut std::process::Command,
+    rows: u16,
+    cols: u16,
+    input: &[u8],
+) -> crate::Result<Vec<u8>> {
+    let spawn_error =
+        |e: nix::Error| crate::Error::Spawn { source: e.into() };
+    let size = nix::pty::Winsize {
+        ws_row: rows,
+        ws_col: cols,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    let pty = nix::pty::openpty(Some(&size), None).map_err(spawn_error)?;
+    let clone = |fd: &std::os::fd::OwnedFd| {
+        fd.try_clone()
+            .map_err(|source| crate::Error::Spawn { source })
+    };
+    command
+        .stdin(clone(&pty.slave)?)
+        .stdout(clone(&pty.slave)?)
+        .stderr(pty.slave);
+    // safety: setsid and ioctl are both async-signal-safe
+    unsafe {
+        command.pre_exec(|| {
+            nix::unistd::setsid()?;
+            tiocsctty(0, 0)?;
+            Ok(())
+        });
+    }
+    let child = command.spawn();
+    // the command holds on to its copies of the pty until it is dropped or
+    // reconfigured, and reads from the pty won't see eof until they are
+    // closed
+    command
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    let mut child = child.map_err(|source| crate::Error::Spawn { source })?;
+
+    let mut pty = std::fs::File::from(pty.master);
+    let fd = std::os::fd::AsRawFd::as_raw_fd(&pty);
+    let flags = nix::fcntl::OFlag::from_bits_truncate(
+        nix::fcntl::fcntl(fd, nix::fcntl::FcntlArg::F_GETFL)
+            .map_err(spawn_error)?,
+    );
+    nix::fcntl::fcntl(
+        fd,
+        nix::fcntl::FcntlArg::F_SETFL(flags | nix::fcntl::OFlag::O_NONBLOCK),
+    )
+    .map_err(spawn_error)?;
+
+    // input is written as the pty is able to accept it, interleaved with
+    // reading the output, since writing it all up front could block
+    // forever if the command fills up the output buffer before reading it
+    let mut input = input;
+    let mut writer = crate::blocking::Writer::new(vec![]);
+    let mut buf = [0; 4096];
+    loop {
+        let mut events = nix::poll::PollFlags::POLLIN;
+        if !input.is_empty() {
+            events |= nix::poll::PollFlags::POLLOUT;
+        }
+        let mut fds = [nix::poll::PollFd::new(
+            std::os::fd::AsFd::as_fd(&pty),
+            events,
+        )];
+        match nix::poll::poll(&mut fds, nix::poll::PollTimeout::NONE) {
+            Ok(_) | Err(nix::errno::Errno::EINTR) => {}
+            Err(e) => return Err(crate::Error::Read { source: e.into() }),
+        }
+        let revents = fds[0].revents().unwrap_or(events);
+
+        if revents.contains(nix::poll::PollFlags::POLLOUT) {
+            match std::io::Write::write(&mut pty, input) {
+                Ok(n) => input = &input[n..],
+                Err(e) if is_retry(&e) => {}
+                // the command exited without reading all of its input
+                Err(e) if e.raw_os_error() == Some(nix::libc::EIO) => {
+                    input = &[];
+                }
+                Err(source) => return Err(crate::Error::Write { source }),
+            }
+        }
+        if revents.intersects(
+            nix::poll::PollFlags::POLLIN
+                | nix::poll::PollFlags::POLLHUP
+                | nix::poll::PollFlags::POLLERR,
+        ) {
+            match std::io::Read::read(&mut pty, &mut buf) {
+                Ok(0) => break,
+                Ok(n) => writer.frame(&buf[..n])?,
+                Err(e) if is_retry(&e) => {}
+                // reads return EIO once the other end of the pty is closed
+                Err(e) if e.raw_os_error() == Some(nix::libc::EIO) => break,
+                Err(source) => return Err(crate::Error::Read { source }),
+            }
+        }
+    }
+    child
+        .wait()
+        .map_err(|source| crate::Error::Read { source })?;
+    writer.into_inner()
+}
+
+fn is_retry(e: &std::io::Error) -> bool {
+    matches!(
+        e.kind(),
+        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::Interrupted
+    )
+}
+
+/// A golden recording stored on disk, which recordings made during tests
+/// are compared against.
+///
+/// By default, recordings are compared by their concatenated output (see
+/// [`CompareMode::Bytes`](crate::CompareMode::Bytes)), ignoring both
+/// timing and how the output was split into frames, since neither is
+/// deterministic when running a program under a pty. A different
+/// [`Comparer`](crate::Comparer) can be given with
+/// [`set_comparer`](Self::set_comparer).
+pub struct Golden {
+    path: std::path::PathBuf,
+    rows: u16,
+    cols: u16,
+    input: Vec<u8>,
+    comparer: crate::Comparer,
+}
+
+impl Golden {
+    /// Creates a new [`Golden`] for the recording stored at the given path.
+    /// Relative paths are resolved against the current directory, which is
+    /// the package root when run via `cargo test`.
+    #[must_use]
+    pub fn new(path: impl AsRef<std::path::Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            rows: 24,
+            cols: 80,
+            input: vec![],
+            comparer: crate::Comparer::new(crate::CompareMode::Bytes),
+        }
+    }
+
+    /// Sets the size of the pty used by
+    /// [`assert_command`](Self::assert_command). Defaults to 24x80.
+    pub fn set_size(&mut self, rows: u16, cols: u16) {
+        self.rows = rows;
+        self.cols = cols;
+    }
+
+    /// Sets the data written to the pty when the command is started by
+    /// [`assert_command`](Self::assert_command). Defaults to nothing.
+    pub fn set_input(&mut self, input: impl Into<Vec<u8>>) {
+        self.input = input.into();
+    }
+
+    /// Sets how recordings are compared against the golden recording.
+    pub fn set_comparer(&mut self, comparer: crate::Comparer) {
+        self.comparer = comparer;
+    }
+
+    /// Runs the command under a pty (see [`record`]), and asserts that its
+    /// output matches the golden recording.
+    ///
+    /// # Panics
+    /// Panics if the command can't be run, if the recording doesn't match,
+    /// or if the golden recording doesn't exist. If `TTYREC_UPDATE=1` is
+    /// set in the environment, the golden recording is replaced with the
+    /// new recording instead.
+    pub fn assert_command(&self, command: &mut std::process::Command) {
+        let recording = record(command, self.rows, self.cols, &self.input)
+            .unwrap_or_else(|e| panic!("failed to record {command:?}: {e}"));
+        self.assert_recording(&recording);
+    }
+
+    /// Asserts that the given ttyrec data matches the golden recording.
+    ///
+    /// # Panics
+    /// Panics if the recording doesn't match, or if the golden recording
+    /// doesn't exist. If `TTYREC_UPDATE=1` is set in the environment, the
+    /// golden recording is replaced with the new recording instead.
+    pub fn assert_recording(&self, recording: &[u8]) {
+        let update = std::env::var_os(UPDATE_VAR).is_some_and(|v| v == "1");
+        if let Err(message) = self.check(recording, update) {
+            panic!("{message}");
+        }
+    }
+
+    fn check(&self, recording: &[u8], update: bool) -> Result<(), String> {
+        let path = self.path.display();
+        if update {
+            if let Some(dir) = self.path.parent() {
+                std::fs::create_dir_all(dir).map_err(|e| {
+                    format!("failed to create {}: {e}", dir.display())
+                })?;
+            }
+            return std::fs::write(&self.path, recording)
+                .map_err(|e| format!("failed to write {path}: {e}"));
+        }
+
+        let golden = match std::fs::read(&self.path) {
+            Ok(golden) => golden,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(format!(
+                    "golden recording {path} does not exist (run with \
+                     {UPDATE_VAR}=1 to create it)"
+                ));
+            }
+            Err(e) => return Err(format!("failed to read {path}: {e}")),
+        };
+        self.comparer
+            .compare(&frames(&golden), &frames(recording))
+            .map_or(Ok(()), |divergence| {
+                Err(format!(
+                    "recording does not match {path}:\n{divergence}\n(run \
+                     with {UPDATE_VAR}=1 to update it)"
+                ))
+            })
+    }
+}
+
+fn frames(data: &[u8]) -> Vec<crate::frame::Frame> {
+    let mut parser = crate::Parser::new();
+    parser.add_bytes(data);
+    std::iter::from_fn(|| parser.next_frame()).collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_basic() {
+        let recording = record(
+            std::process::Command::new("sh")
+                .arg("-c")
+                .arg("stty size; test -t 0 && read line && echo \"$line\""),
+            5,
+            40,
+            b"hello\n",
+        )
+        .unwrap();
+        let output: Vec<u8> = frames(&recording)
+            .into_iter()
+            .flat_map(|f| f.data)
+            .collect();
+        // input is echoed by the pty (at some point relative to the output
+        // of stty, depending on timing), and newlines are translated
+        let output = String::from_utf8(output).unwrap();
+        let mut lines: Vec<_> = output.split_terminator("\r\n").collect();
+        lines.sort_unstable();
+        assert_eq!(lines, ["5 40", "hello", "hello"], "{output:?}");
+        assert!(output.ends_with("\r\n"));
+
+        let path = std::env::temp_dir().join(format!(
+            "ttyrec-test-{}/golden.ttyrec",
+            std::process::id()
+        ));
+        let golden = Golden::new(&path);
+        assert!(golden
+            .check(&recording, false)
+            .unwrap_err()
+            .contains("does not exist"));
+        golden.check(&recording, true).unwrap();
+        golden.check(&recording, false).unwrap();
+
+        let mut other = crate::blocking::Writer::new(vec![]);
+        other.frame(b"hello\r\n5 40\r\nhullo\r\n").unwrap();
+        let message = golden
+            .check(&other.into_inner().unwrap(), false)
+            .unwrap_err();
+        assert!(message.contains("output differs"), "{message}");
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_large_input() {
+        // more input than fits in the pty buffers, for a command which
+        // produces output before reading it
+        let input = "x".repeat(1000) + "\n";
+        let recording = record(
+            std::process::Command::new("sh")
+                .arg("-c")
+                .arg("head -c 100000 /dev/zero; head -c 100100 >/dev/null"),
+            5,
+            40,
+            input.repeat(100).as_bytes(),
+        )
+        .unwrap();
+        let len: usize =
+            frames(&recording).iter().map(|f| f.data.len()).sum();
+        assert!(len >= 100_000);
+
+        // and for a command which exits without reading it at all
+        let recording = record(
+            &mut std::process::Command::new("true"),
+            5,
+            40,
+            input.repeat(100).as_bytes(),
+        )
+        .unwrap();
+        assert!(frames(&recording).len() < 1000);
+    }
+}
@@ -0,0 +1,227 @@
+/// Which point in a recording a [`Thumbnail`] shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThumbnailPosition {
+    /// The screen as it was at the given time, relative to the first frame.
+    At(std::time::Duration),
+
+    /// The screen as it was at the end of the recording.
+    #[default]
+    Last,
+
+    /// The screen with the most visible (non-whitespace) characters on it.
+    /// If several screens have the same amount, the earliest one is used.
+    MostContent,
+}
+
+/// Renders a single screen from a recording as a PNG image.
+///
+/// Frames are run through a terminal emulator and drawn with the same
+/// embedded bitmap font as [`Animation`](crate::Animation), so that a
+/// preview of a recording can be generated without any external tools.
+/// Window resizes recorded as [`ExtendedFrame`](crate::ExtendedFrame)s are
+/// applied to the emulated terminal, but the image size is always based on
+/// the size given to [`new`](Self::new) (or on
+/// [`set_dimensions`](Self::set_dimensions), if set).
+///
+/// Add frames in stream order with [`add_frame`](Self::add_frame), and then
+/// write the image with [`write_png`](Self::write_png). Frames after the
+/// chosen position are ignored, so when using
+/// [`ThumbnailPosition::At`], reading can stop as soon as
+/// [`is_done`](Self::is_done) returns true.
+pub struct Thumbnail {
+    parser: vt100::Parser,
+    position: ThumbnailPosition,
+    rows: u16,
+    cols: u16,
+    palette: crate::Palette,
+    scale: usize,
+    dimensions: Option<(usize, usize)>,
+    start_time: Option<std::time::Duration>,
+    done: bool,
+    best: Option<(usize, Vec<u8>, u16, u16)>,
+}
+
+impl Thumbnail {
+    /// Creates a new [`Thumbnail`] for a terminal of the given size, showing
+    /// the screen at the given position.
+    #[must_use]
+    pub fn new(rows: u16, cols: u16, position: ThumbnailPosition) -> Self {
+        Self {
+            parser: vt100::Parser::new(rows, cols, 0),
+            position,
+            rows,
+            cols,
+            palette: crate::Palette::new(),
+            scale: 1,
+            dimensions: None,
+            start_time: None,
+            done: false,
+            best: None,
+        }
+    }
+
+    /// Sets the colors used to draw the screen.
+    pub fn set_palette(&mut self, palette: crate::Palette) {
+        self.palette = palette;
+    }
+
+    /// Sets the size of each pixel of the font, in image pixels. Each
+    /// character cell is 6x10 font pixels. Defaults to 1. Ignored if
+    /// [`set_dimensions`](Self::set_dimensions) is used.
+    pub fn set_scale(&mut self, scale: u8) {
+        self.scale = usize::from(scale.max(1));
+    }
+
+    /// Sets the exact size of the image in pixels. The rendered screen is
+    /// scaled to fit, without preserving its aspect ratio. Passing [`None`]
+    /// (the default) uses the natural size of the rendered screen.
+    pub fn set_dimensions(&mut self, dimensions: Option<(u32, u32)>) {
+        self.dimensions = dimensions.and_then(|(width, height)| {
+            Some((
+                usize::try_from(width).ok()?.max(1),
+                usize::try_from(height).ok()?.max(1),
+            ))
+        });
+    }
+
+    /// Adds the next frame in the stream.
+    pub fn add_frame(&mut self, frame: &crate::frame::Frame) {
+        if self.done {
+            return;
+        }
+        let start_time = *self.start_time.get_or_insert(frame.time);
+        if let ThumbnailPosition::At(time) = self.position {
+            if frame.time.saturating_sub(start_time) > time {
+                self.done = true;
+                return;
+            }
+        }
+
+        if crate::extended::is_extension(&frame.data) {
+            if let crate::FrameKind::Resize { rows, cols } =
+                crate::ExtendedFrame::from(frame.clone()).kind
+            {
+                self.parser.screen_mut().set_size(rows, cols);
+            }
+        }
+        self.parser.process(&frame.data);
+
+        if self.position == ThumbnailPosition::MostContent {
+            let screen = self.parser.screen();
+            let content = screen
+                .contents()
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .count();
+            if self.best.as_ref().is_none_or(|best| content > best.0) {
+                let (rows, cols) = screen.size();
+                self.best =
+                    Some((content, screen.state_formatted(), rows, cols));
+            }
+        }
+    }
+
+    /// Returns true if no further frames can change the image.
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Writes the image as a PNG.
+    ///
+    /// # Errors
+    /// * [`Error::Encode`](crate::Error::Encode): The image was too large
+    ///   for the PNG format, or there was an error writing the image.
+    pub fn write_png<W: std::io::Write>(
+        &self,
+        output: W,
+    ) -> crate::Result<()> {
+        let image = self.image();
+        let width = u32::try_from(image.width)
+            .map_err(|_| crate::render::too_large())?;
+        let height = u32::try_from(image.height)
+            .map_err(|_| crate::render::too_large())?;
+        let mut encoder = png::Encoder::new(output, width, height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder
+            .write_header()
+            .map_err(crate::render::encode_error)?;
+        writer
+            .write_image_data(&image.pixels)
+            .map_err(crate::render::encode_error)?;
+        writer.finish().map_err(crate::render::encode_error)
+    }
+
+    fn image(&self) -> crate::render::Image {
+        let best = self.best.as_ref().map(|(_, state, rows, cols)| {
+            let mut parser = vt100::Parser::new(*rows, *cols, 0);
+            parser.process(state);
+            parser
+        });
+        let screen = best.as_ref().unwrap_or(&self.parser).screen();
+        let image = crate::render::render(
+            screen,
+            &self.palette,
+            self.rows,
+            self.cols,
+            self.scale,
+        );
+        match self.dimensions {
+            Some((width, height)) => image.resize(width, height),
+            None => image,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    fn thumbnail(position: ThumbnailPosition) -> Thumbnail {
+        let mut thumbnail = Thumbnail::new(2, 4, position);
+        for (millis, data) in [
+            (5000, &b"\x1b[?25l\x1b[41mab\x1b[m"[..]),
+            (6000, b"cd"),
+            (7000, b"\x1b[H\x1b[2J"),
+        ] {
+            thumbnail.add_frame(&crate::frame::Frame {
+                time: std::time::Duration::from_millis(millis),
+                data: data.to_vec(),
+            });
+        }
+        thumbnail
+    }
+
+    #[test]
+    fn test_basic() {
+        let red = crate::Palette::new().color(1);
+        let black = crate::Palette::new().background();
+
+        let at = thumbnail(ThumbnailPosition::At(
+            std::time::Duration::from_millis(1500),
+        ));
+        assert!(at.is_done());
+        let image = at.image();
+        assert_eq!((image.width, image.height), (24, 20));
+        assert_eq!(image.pixels[..3], red);
+
+        let last = thumbnail(ThumbnailPosition::Last);
+        assert!(!last.is_done());
+        assert_eq!(last.image().pixels[..3], black);
+
+        let most = thumbnail(ThumbnailPosition::MostContent);
+        assert_eq!(most.image().pixels[..3], red);
+
+        let mut resized = thumbnail(ThumbnailPosition::MostContent);
+        resized.set_dimensions(Some((12, 5)));
+        let image = resized.image();
+        assert_eq!((image.width, image.height), (12, 5));
+        assert_eq!(image.pixels[..3], red);
+
+        let mut png = vec![];
+        resized.write_png(&mut png).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+    }
+}
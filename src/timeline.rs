@@ -0,0 +1,207 @@
+// how long the last screen is shown before an animation loops
+const FINAL_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+
+struct Snapshot {
+    state: Vec<u8>,
+    rows: u16,
+    cols: u16,
+    time: std::time::Duration,
+}
+
+// the distinct screen states of a recording, along with when they should be
+// shown during playback. only the serialized states are stored, since
+// keeping a full copy of the screen for every frame would use far more
+// memory.
+pub struct Timeline {
+    parser: vt100::Parser,
+    idle_limit: Option<std::time::Duration>,
+    speed: f64,
+    last_time: Option<std::time::Duration>,
+    elapsed: std::time::Duration,
+    snapshots: Vec<Snapshot>,
+}
+
+impl Timeline {
+    pub fn new(rows: u16, cols: u16) -> Self {
+        let parser = vt100::Parser::new(rows, cols, 0);
+        // the initial state is replaced if the first frame is at time zero
+        let initial = Snapshot {
+            state: parser.screen().state_formatted(),
+            rows,
+            cols,
+            time: std::time::Duration::ZERO,
+        };
+        Self {
+            parser,
+            idle_limit: None,
+            speed: 1.0,
+            last_time: None,
+            elapsed: std::time::Duration::ZERO,
+            snapshots: vec![initial],
+        }
+    }
+
+    // only used by some of the exporters
+    #[cfg_attr(
+        not(any(feature = "gif", feature = "png", feature = "svg")),
+        allow(dead_code)
+    )]
+    pub fn set_idle_limit(&mut self, limit: Option<std::time::Duration>) {
+        self.idle_limit = limit;
+    }
+
+    #[cfg_attr(
+        not(any(feature = "gif", feature = "png", feature = "svg")),
+        allow(dead_code)
+    )]
+    pub fn set_speed(&mut self, speed: f64) {
+        if speed > 0.0 && speed.is_finite() {
+            self.speed = speed;
+        }
+    }
+
+    pub fn add_frame(&mut self, frame: &crate::frame::Frame) {
+        let mut delay =
+            self.last_time.map_or(std::time::Duration::ZERO, |last| {
+                frame.time.saturating_sub(last)
+            });
+        self.last_time = Some(frame.time);
+        if let Some(limit) = self.idle_limit {
+            delay = delay.min(limit);
+        }
+        self.elapsed += delay.div_f64(self.speed);
+
+        if crate::extended::is_extension(&frame.data) {
+            if let crate::FrameKind::Resize { rows, cols } =
+                crate::ExtendedFrame::from(frame.clone()).kind
+            {
+                self.parser.screen_mut().set_size(rows, cols);
+            }
+        }
+        self.parser.process(&frame.data);
+
+        let screen = self.parser.screen();
+        let state = screen.state_formatted();
+        let (rows, cols) = screen.size();
+        if let Some(last) = self.snapshots.last_mut() {
+            if last.state == state && last.rows == rows && last.cols == cols {
+                return;
+            }
+            // the previous state would never be seen
+            if last.time == self.elapsed {
+                self.snapshots.pop();
+            }
+        }
+        self.snapshots.push(Snapshot {
+            state,
+            rows,
+            cols,
+            time: self.elapsed,
+        });
+    }
+
+    // yields each screen state along with the time at which it should be
+    // shown and how long it should be shown for, with times rounded to the
+    // given resolution. states which would be shown for less than the
+    // resolution are skipped.
+    pub fn screens(
+        &self,
+        resolution: std::time::Duration,
+    ) -> impl Iterator<
+        Item = (vt100::Parser, std::time::Duration, std::time::Duration),
+    > + '_ {
+        self.spans(resolution).map(|(snapshot, start, duration)| {
+            let mut parser =
+                vt100::Parser::new(snapshot.rows, snapshot.cols, 0);
+            parser.process(&snapshot.state);
+            (parser, start, duration)
+        })
+    }
+
+    // like screens, but only yields how long each screen is shown for,
+    // without recreating the screens
+    #[cfg(feature = "png")]
+    pub fn durations(
+        &self,
+        resolution: std::time::Duration,
+    ) -> impl Iterator<Item = std::time::Duration> + '_ {
+        self.spans(resolution).map(|(_, _, duration)| duration)
+    }
+
+    fn spans(
+        &self,
+        resolution: std::time::Duration,
+    ) -> impl Iterator<
+        Item = (&Snapshot, std::time::Duration, std::time::Duration),
+    > + '_ {
+        let ticks = move |time: std::time::Duration| {
+            (time.as_nanos() + resolution.as_nanos() / 2)
+                / resolution.as_nanos()
+        };
+        let duration = move |ticks: u128| {
+            u32::try_from(ticks)
+                .map_or(std::time::Duration::MAX, |n| resolution * n)
+        };
+        let end = self
+            .snapshots
+            .last()
+            .map_or(FINAL_DELAY, |last| last.time + FINAL_DELAY);
+        self.snapshots
+            .iter()
+            .enumerate()
+            .filter_map(move |(i, snapshot)| {
+                let next =
+                    self.snapshots.get(i + 1).map_or(end, |next| next.time);
+                let start = ticks(snapshot.time);
+                let next = ticks(next);
+                if next <= start {
+                    return None;
+                }
+                Some((snapshot, duration(start), duration(next - start)))
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_basic() {
+        let mut timeline = Timeline::new(4, 10);
+        timeline.set_idle_limit(Some(std::time::Duration::from_secs(2)));
+        timeline.set_speed(2.0);
+        for (millis, data) in [
+            (0, &b"\x1b[?25l$ "[..]),
+            (1000, b"l"),
+            (1001, b"s"),
+            (11000, b"\r\n\x1b[31mfoo"),
+            (12000, b""),
+        ] {
+            timeline.add_frame(&crate::frame::Frame {
+                time: std::time::Duration::from_millis(millis),
+                data: data.to_vec(),
+            });
+        }
+        let screens: Vec<_> = timeline
+            .screens(std::time::Duration::from_millis(10))
+            .map(|(parser, start, duration)| {
+                (
+                    parser.screen().contents(),
+                    start.as_millis(),
+                    duration.as_millis(),
+                )
+            })
+            .collect();
+        // "l" and "s" are drawn within the same 10ms, and the idle period
+        // after "ls" is capped to 2s and then halved
+        assert_eq!(
+            screens,
+            vec![
+                ("$ ".to_string(), 0, 500),
+                ("$ ls".to_string(), 500, 1000),
+                ("$ ls\nfoo".to_string(), 1500, 1000),
+            ]
+        );
+    }
+}
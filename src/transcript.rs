@@ -0,0 +1,204 @@
+const TAB_WIDTH: usize = 8;
+
+/// A single line of a [`Transcript`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    /// Timestamp of the frame in which the line started.
+    pub time: std::time::Duration,
+
+    /// The text of the line, without a trailing newline.
+    pub text: String,
+}
+
+impl Line {
+    /// Returns the text of the line, prefixed with its timestamp (in the
+    /// form `[HH:MM:SS.mmm]`).
+    #[must_use]
+    pub fn timestamped(&self) -> String {
+        let secs = self.time.as_secs();
+        format!(
+            "[{:02}:{:02}:{:02}.{:03}] {}",
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60,
+            self.time.subsec_millis(),
+            self.text
+        )
+    }
+}
+
+impl std::fmt::Display for Line {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// Converts a stream of frames into a plain text transcript.
+///
+/// Terminal escape sequences are removed, and carriage returns, backspaces,
+/// and tabs are applied to the current line the way a terminal would, so
+/// text which was overwritten on screen (such as progress bars or edited
+/// shell input) appears in its final form. Cursor movement via escape
+/// sequences is not emulated. UTF-8 characters which are split across
+/// frames are handled correctly, and invalid UTF-8 is replaced with
+/// `U+FFFD`.
+///
+/// Designed to be used in a streaming fashion, similar to
+/// [`Parser`](crate::Parser): add frames in stream order with
+/// [`add_frame`](Self::add_frame), and then call
+/// [`next_line`](Self::next_line) to retrieve completed lines. Once the
+/// stream has ended, call [`finish`](Self::finish) to complete the final
+/// line.
+#[derive(Default)]
+pub struct Transcript {
+    stripper: crate::strip::Stripper,
+    utf8: Vec<u8>,
+    line: Vec<char>,
+    cursor: usize,
+    start: Option<std::time::Duration>,
+    last_time: std::time::Duration,
+    lines: std::collections::VecDeque<Line>,
+}
+
+impl Transcript {
+    /// Creates a new [`Transcript`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the next frame in the stream.
+    pub fn add_frame(&mut self, frame: &crate::frame::Frame) {
+        self.last_time = frame.time;
+        for &c in &frame.data {
+            if !self.stripper.keep(c) {
+                continue;
+            }
+            if c.is_ascii() {
+                if !self.utf8.is_empty() {
+                    self.utf8.clear();
+                    self.put(std::char::REPLACEMENT_CHARACTER, frame.time);
+                }
+                self.ascii(c, frame.time);
+                continue;
+            }
+
+            self.utf8.push(c);
+            match std::str::from_utf8(&self.utf8) {
+                Ok(s) => {
+                    let chars: Vec<_> = s.chars().collect();
+                    self.utf8.clear();
+                    for c in chars {
+                        self.put(c, frame.time);
+                    }
+                }
+                Err(e) if e.error_len().is_some() => {
+                    // the byte which made the sequence invalid may be the
+                    // start of a new sequence
+                    let restart =
+                        self.utf8.len() > 1 && (0xc2..=0xf4).contains(&c);
+                    self.utf8.clear();
+                    self.put(std::char::REPLACEMENT_CHARACTER, frame.time);
+                    if restart {
+                        self.utf8.push(c);
+                    }
+                }
+                Err(_) => {}
+            }
+        }
+    }
+
+    /// Returns the next completed line, if one is available.
+    pub fn next_line(&mut self) -> Option<Line> {
+        self.lines.pop_front()
+    }
+
+    /// Signals that the stream has ended, completing the final line if it
+    /// contains any text.
+    pub fn finish(&mut self) {
+        if !self.utf8.is_empty() {
+            self.utf8.clear();
+            self.put(std::char::REPLACEMENT_CHARACTER, self.last_time);
+        }
+        if self.start.is_some() {
+            self.end_line(self.last_time);
+        }
+    }
+
+    fn ascii(&mut self, c: u8, time: std::time::Duration) {
+        match c {
+            b'\n' => self.end_line(time),
+            b'\r' => self.cursor = 0,
+            b'\x08' => self.cursor = self.cursor.saturating_sub(1),
+            b'\t' => {
+                self.start.get_or_insert(time);
+                self.cursor = (self.cursor / TAB_WIDTH + 1) * TAB_WIDTH;
+            }
+            b' '..=b'~' => self.put(char::from(c), time),
+            _ => {}
+        }
+    }
+
+    fn put(&mut self, c: char, time: std::time::Duration) {
+        self.start.get_or_insert(time);
+        if self.cursor < self.line.len() {
+            self.line[self.cursor] = c;
+        } else {
+            self.line.resize(self.cursor, ' ');
+            self.line.push(c);
+        }
+        self.cursor += 1;
+    }
+
+    fn end_line(&mut self, time: std::time::Duration) {
+        let text: String = self.line.iter().collect();
+        self.lines.push_back(Line {
+            time: self.start.unwrap_or(time),
+            text: text.trim_end().to_string(),
+        });
+        self.line.clear();
+        self.cursor = 0;
+        self.start = None;
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_basic() {
+        let mut transcript = Transcript::new();
+        for (i, chunk) in [
+            &b"$ ls\x1b[31m -l\x1b[m\r\n"[..],
+            b"caf\xc3",
+            b"\xa9\tx\r\n",
+            b"50%\r100%\r\n",
+            b"$ rm -rx\x08f /",
+            b"\x08\x08 \xff\xe2\x82",
+        ]
+        .iter()
+        .enumerate()
+        {
+            transcript.add_frame(&crate::frame::Frame {
+                time: std::time::Duration::from_millis(
+                    u64::try_from(i).unwrap() * 1500,
+                ),
+                data: chunk.to_vec(),
+            });
+        }
+        transcript.finish();
+        let lines: Vec<_> =
+            std::iter::from_fn(|| transcript.next_line()).collect();
+        assert_eq!(
+            lines.iter().map(Line::timestamped).collect::<Vec<_>>(),
+            vec![
+                "[00:00:00.000] $ ls -l",
+                "[00:00:01.500] caf\u{e9}    x",
+                "[00:00:04.500] 100%",
+                "[00:00:06.000] $ rm -rf \u{fffd}\u{fffd}",
+            ]
+        );
+    }
+}
@@ -0,0 +1,184 @@
+/// A problem found in a stream of frames by a [`Validator`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Index of the offending frame in the stream.
+    pub index: usize,
+
+    /// Timestamp of the offending frame.
+    pub time: std::time::Duration,
+
+    /// What is wrong with the frame.
+    pub kind: DiagnosticKind,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "frame {} (at {:?}): ", self.index, self.time)?;
+        match self.kind {
+            DiagnosticKind::NonMonotonic { previous } => write!(
+                f,
+                "timestamp goes backwards (previous frame was at {previous:?})"
+            ),
+            DiagnosticKind::Empty => write!(f, "frame contains no data"),
+            DiagnosticKind::Jump { gap } => {
+                write!(f, "implausible gap of {gap:?} since previous frame")
+            }
+        }
+    }
+}
+
+/// The kinds of problems reported by a [`Validator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// The frame has an earlier timestamp than the frame before it.
+    NonMonotonic { previous: std::time::Duration },
+
+    /// The frame contains no data.
+    Empty,
+
+    /// The frame comes after a gap longer than the configured maximum (see
+    /// [`Validator::set_max_gap`]).
+    Jump { gap: std::time::Duration },
+}
+
+/// Checks a stream of frames for timestamp problems.
+///
+/// Frames should be passed to [`check`](Self::check) in stream order, and
+/// any problems with each frame are returned as a list of [`Diagnostic`]
+/// values. Timestamps are checked for going backwards (which can happen if
+/// the recording host's wall clock was stepped during the recording), and
+/// optionally for implausibly long gaps between frames.
+#[derive(Debug, Clone, Default)]
+pub struct Validator {
+    max_gap: Option<std::time::Duration>,
+    index: usize,
+    previous: Option<std::time::Duration>,
+    // the latest timestamp seen so far, which gaps are measured from, so
+    // that frames after one which went backwards aren't reported as jumps
+    latest: Option<std::time::Duration>,
+}
+
+impl Validator {
+    /// Creates a new [`Validator`] instance.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the longest gap between consecutive frames which is considered
+    /// plausible. Gaps longer than this will be reported as
+    /// [`DiagnosticKind::Jump`]. Defaults to [`None`], which disables this
+    /// check.
+    pub fn set_max_gap(&mut self, max_gap: Option<std::time::Duration>) {
+        self.max_gap = max_gap;
+    }
+
+    /// Checks the next frame in the stream, returning any problems found.
+    pub fn check(&mut self, frame: &crate::frame::Frame) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        let mut report = |kind| {
+            diagnostics.push(Diagnostic {
+                index: self.index,
+                time: frame.time,
+                kind,
+            });
+        };
+
+        if let Some(previous) = self.previous {
+            if frame.time < previous {
+                report(DiagnosticKind::NonMonotonic { previous });
+            }
+        }
+        if let Some(gap) = self
+            .latest
+            .and_then(|latest| frame.time.checked_sub(latest))
+        {
+            if self.max_gap.is_some_and(|max_gap| gap > max_gap) {
+                report(DiagnosticKind::Jump { gap });
+            }
+        }
+        if frame.data.is_empty() {
+            report(DiagnosticKind::Empty);
+        }
+
+        self.index += 1;
+        self.previous = Some(frame.time);
+        self.latest = Some(
+            self.latest
+                .map_or(frame.time, |latest| latest.max(frame.time)),
+        );
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_basic() {
+        let frames: Vec<_> =
+            [(0, "a"), (5, "b"), (3, "c"), (4, ""), (606, "d")]
+                .into_iter()
+                .map(|(secs, data)| crate::frame::Frame {
+                    time: std::time::Duration::from_secs(secs),
+                    data: data.as_bytes().to_vec(),
+                })
+                .collect();
+
+        let mut validator = Validator::new();
+        validator.set_max_gap(Some(std::time::Duration::from_secs(30)));
+        let diagnostics: Vec<_> =
+            frames.iter().flat_map(|f| validator.check(f)).collect();
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic {
+                    index: 2,
+                    time: std::time::Duration::from_secs(3),
+                    kind: DiagnosticKind::NonMonotonic {
+                        previous: std::time::Duration::from_secs(5)
+                    },
+                },
+                Diagnostic {
+                    index: 3,
+                    time: std::time::Duration::from_secs(4),
+                    kind: DiagnosticKind::Empty,
+                },
+                Diagnostic {
+                    index: 4,
+                    time: std::time::Duration::from_secs(606),
+                    kind: DiagnosticKind::Jump {
+                        gap: std::time::Duration::from_secs(601)
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_backwards() {
+        let mut validator = Validator::new();
+        validator.set_max_gap(Some(std::time::Duration::from_secs(100)));
+        let diagnostics: Vec<_> = [0, 100, 2, 103]
+            .into_iter()
+            .flat_map(|secs| {
+                validator.check(&crate::frame::Frame {
+                    time: std::time::Duration::from_secs(secs),
+                    data: b"a".to_vec(),
+                })
+            })
+            .collect();
+        // the frame after the one which went backwards isn't a jump
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic {
+                index: 2,
+                time: std::time::Duration::from_secs(2),
+                kind: DiagnosticKind::NonMonotonic {
+                    previous: std::time::Duration::from_secs(100)
+                },
+            }]
+        );
+    }
+}
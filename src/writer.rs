@@ -1,27 +1,100 @@
-use tokio::io::AsyncWriteExt as _;
-
 /// Writes ttyrec frames to a [`tokio::io::AsyncWrite`] instance.
+///
+/// By default, each frame is written to the output stream as soon as it is
+/// created. A [`Writer`](Self) created with
+/// [`with_capacity`](Self::with_capacity) instead collects frames in an
+/// internal buffer and only writes them out once the buffer is full, or
+/// when [`flush`](Self::flush) is called. Note that buffered frames which
+/// have not yet been written out will be lost if the [`Writer`](Self) is
+/// dropped without being flushed. If writing to the output stream fails,
+/// the frames which weren't written are kept in the buffer, to be written
+/// by the next write or flush.
+///
+/// With the `"sink"` feature enabled, this also implements
+/// `futures_sink::Sink` for [`Frame`](crate::Frame) values, which writes
+/// them as is, similar to [`write_frame`](Self::write_frame).
 pub struct Writer<T: tokio::io::AsyncWrite> {
     output: T,
-    creator: crate::Creator,
+    buf: crate::buffer::Buffer,
 }
 
 impl<T: tokio::io::AsyncWrite + std::marker::Unpin + Send> Writer<T> {
     /// Creates a new [`Writer`](Self) from a [`tokio::io::AsyncWrite`]
     /// instance.
     pub fn new(output: T) -> Self {
+        Self::with_creator(output, crate::Creator::new())
+    }
+
+    /// Creates a new [`Writer`](Self) which uses the given
+    /// [`Creator`](crate::Creator) to timestamp frames.
+    ///
+    /// This can be used to write absolute timestamps, via
+    /// [`Creator::new_absolute`](crate::Creator::new_absolute).
+    pub fn with_creator(output: T, creator: crate::Creator) -> Self {
         Self {
             output,
-            creator: crate::Creator::new(),
+            buf: crate::buffer::Buffer::new(creator, 0),
         }
     }
 
+    /// Creates a new [`Writer`](Self) from a [`tokio::io::AsyncWrite`]
+    /// instance, which buffers up to `capacity` bytes of frame data before
+    /// writing to the output stream.
+    pub fn with_capacity(capacity: usize, output: T) -> Self {
+        let mut writer = Self::new(output);
+        writer.set_capacity(capacity);
+        writer
+    }
+
+    /// Sets the number of bytes of frame data which are buffered before
+    /// being written to the output stream. Defaults to 0 (which writes each
+    /// frame as soon as it is created), or the capacity given to
+    /// [`with_capacity`](Self::with_capacity).
+    ///
+    /// This can be combined with [`with_creator`](Self::with_creator) to
+    /// buffer frames with absolute timestamps.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.buf.set_capacity(capacity);
+    }
+
+    /// Sets the maximum amount of data written in a single frame. Frames
+    /// with more data than this are split into several frames with the same
+    /// timestamp, avoiding splitting UTF-8 characters and terminal escape
+    /// sequences where possible. Defaults to [`None`], which doesn't split
+    /// frames.
+    ///
+    /// See
+    /// [`Encoder::set_max_frame_size`](crate::Encoder::set_max_frame_size)
+    /// for details.
+    pub fn set_max_frame_size(&mut self, max_frame_size: Option<usize>) {
+        self.buf.encoder.set_max_frame_size(max_frame_size);
+    }
+
+    /// Sets how timestamps are rounded to the microsecond precision of the
+    /// ttyrec format. Defaults to
+    /// [`Rounding::Truncate`](crate::Rounding::Truncate).
+    ///
+    /// See [`Encoder::set_rounding`](crate::Encoder::set_rounding).
+    pub fn set_rounding(&mut self, rounding: crate::Rounding) {
+        self.buf.encoder.set_rounding(rounding);
+    }
+
+    /// Sets whether frames with timestamps which don't fit in an ordinary
+    /// header should be written with an extended header. Defaults to
+    /// `false`.
+    ///
+    /// See
+    /// [`Encoder::set_extended_header`](crate::Encoder::set_extended_header).
+    pub fn set_extended_header(&mut self, extended: bool) {
+        self.buf.encoder.set_extended_header(extended);
+    }
+
     /// Writes a new frame to the output stream, using the current time and
     /// given data.
     ///
     /// # Errors
     /// * [`Error::Write`](crate::Error::Write): There was an error writing to
-    /// the input stream.
+    ///   the input stream.
     pub async fn frame(&mut self, data: &[u8]) -> crate::Result<()> {
         self.frame_at(std::time::Instant::now(), data).await
     }
@@ -31,17 +104,313 @@ impl<T: tokio::io::AsyncWrite + std::marker::Unpin + Send> Writer<T> {
     ///
     /// # Errors
     /// * [`Error::Write`](crate::Error::Write): There was an error writing to
-    /// the input stream.
+    ///   the input stream.
     pub async fn frame_at(
         &mut self,
         cur_time: std::time::Instant,
         data: &[u8],
     ) -> crate::Result<()> {
-        let frame = self.creator.frame_at(cur_time, data);
-        let bytes: Vec<u8> = frame.try_into()?;
-        self.output
-            .write_all(&bytes)
-            .await
+        let time = self.buf.creator.time_at(cur_time);
+        self.write_data(time, data).await
+    }
+
+    /// Writes a new frame to the output stream, using the given data at the
+    /// given amount of time after the previous frame.
+    ///
+    /// See [`Creator::frame_after`](crate::Creator::frame_after).
+    ///
+    /// # Errors
+    /// * [`Error::Write`](crate::Error::Write): There was an error writing to
+    ///   the input stream.
+    pub async fn frame_after(
+        &mut self,
+        delay: std::time::Duration,
+        data: &[u8],
+    ) -> crate::Result<()> {
+        let time = self.buf.creator.time_after(delay);
+        self.write_data(time, data).await
+    }
+
+    /// Writes an existing frame to the output stream, as is.
+    ///
+    /// Note that the timestamp of the frame is not adjusted at all, so
+    /// mixing this with the other methods for writing frames is only useful
+    /// if the timestamps of the given frames are consistent with those
+    /// generated by this writer's [`Creator`](crate::Creator).
+    ///
+    /// # Errors
+    /// * [`Error::Write`](crate::Error::Write): There was an error writing to
+    ///   the input stream.
+    /// * [`Error::FrameTooBig`](crate::Error::FrameTooBig): The frame
+    ///   contains too much data to be represented in a ttyrec file, and no
+    ///   maximum frame size was set with
+    ///   [`set_max_frame_size`](Self::set_max_frame_size).
+    /// * [`Error::FrameTooLong`](crate::Error::FrameTooLong): The frame
+    ///   timestamp is too large to be represented in a ttyrec file.
+    pub async fn write_frame(
+        &mut self,
+        frame: &crate::Frame,
+    ) -> crate::Result<()> {
+        self.write_data(frame.time, &frame.data).await
+    }
+
+    /// Writes any buffered frames to the output stream, and then flushes
+    /// the output stream.
+    ///
+    /// # Errors
+    /// * [`Error::Write`](crate::Error::Write): There was an error writing to
+    ///   the input stream.
+    pub async fn flush(&mut self) -> crate::Result<()> {
+        std::future::poll_fn(|cx| self.poll_flush_output(cx)).await
+    }
+
+    /// Writes any buffered frames to the output stream, and then shuts down
+    /// the output stream.
+    ///
+    /// # Errors
+    /// * [`Error::Write`](crate::Error::Write): There was an error writing to
+    ///   the input stream.
+    pub async fn shutdown(&mut self) -> crate::Result<()> {
+        std::future::poll_fn(|cx| self.poll_shutdown_output(cx)).await
+    }
+
+    /// Returns a reference to the underlying output stream.
+    pub fn get_ref(&self) -> &T {
+        &self.output
+    }
+
+    /// Returns a mutable reference to the underlying output stream.
+    ///
+    /// Note that writing directly to the output stream while there are
+    /// buffered frames will corrupt the ttyrec stream.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.output
+    }
+
+    /// Flushes any buffered frames and returns the underlying output stream.
+    ///
+    /// # Errors
+    /// * [`Error::Write`](crate::Error::Write): There was an error writing to
+    ///   the input stream.
+    pub async fn into_inner(mut self) -> crate::Result<T> {
+        self.flush().await?;
+        Ok(self.output)
+    }
+
+    /// Returns the timestamp for a frame created at the given time,
+    /// according to this writer's [`Creator`](crate::Creator).
+    pub(crate) fn time_at(
+        &mut self,
+        cur_time: std::time::Instant,
+    ) -> std::time::Duration {
+        self.buf.creator.time_at(cur_time)
+    }
+
+    pub(crate) async fn write_data(
+        &mut self,
+        time: std::time::Duration,
+        data: &[u8],
+    ) -> crate::Result<()> {
+        self.buf.push(time, data)?;
+        if self.buf.is_full() {
+            self.write_buf().await?;
+        }
+        Ok(())
+    }
+
+    async fn write_buf(&mut self) -> crate::Result<()> {
+        std::future::poll_fn(|cx| self.poll_write_buf(cx)).await
+    }
+
+    fn poll_write_buf(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<crate::Result<()>> {
+        self.buf.poll_write(cx, |cx, data| {
+            std::pin::Pin::new(&mut self.output).poll_write(cx, data)
+        })
+    }
+
+    fn poll_flush_output(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<crate::Result<()>> {
+        std::task::ready!(self.poll_write_buf(cx))?;
+        std::pin::Pin::new(&mut self.output)
+            .poll_flush(cx)
             .map_err(|source| crate::Error::Write { source })
     }
+
+    fn poll_shutdown_output(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<crate::Result<()>> {
+        std::task::ready!(self.poll_write_buf(cx))?;
+        std::pin::Pin::new(&mut self.output)
+            .poll_shutdown(cx)
+            .map_err(|source| crate::Error::Write { source })
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<T: tokio::io::AsyncWrite + std::marker::Unpin + Send>
+    futures_sink::Sink<crate::Frame> for Writer<T>
+{
+    type Error = crate::Error;
+
+    fn poll_ready(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<crate::Result<()>> {
+        let this = std::pin::Pin::into_inner(self);
+        if this.buf.is_full() {
+            this.poll_write_buf(cx)
+        } else {
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    fn start_send(
+        self: std::pin::Pin<&mut Self>,
+        frame: crate::Frame,
+    ) -> crate::Result<()> {
+        let this = std::pin::Pin::into_inner(self);
+        this.buf.push(frame.time, &frame.data)
+    }
+
+    fn poll_flush(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<crate::Result<()>> {
+        std::pin::Pin::into_inner(self).poll_flush_output(cx)
+    }
+
+    fn poll_close(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<crate::Result<()>> {
+        std::pin::Pin::into_inner(self).poll_shutdown_output(cx)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+
+    #[derive(Default)]
+    struct Output {
+        data: Vec<u8>,
+        fail_after: Option<usize>,
+        closed: bool,
+    }
+
+    impl tokio::io::AsyncWrite for Output {
+        fn poll_write(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+            buf: &[u8],
+        ) -> std::task::Poll<std::io::Result<usize>> {
+            let this = std::pin::Pin::into_inner(self);
+            if let Some(fail_after) = &mut this.fail_after {
+                if *fail_after == 0 {
+                    return std::task::Poll::Ready(Err(
+                        std::io::Error::other("failed"),
+                    ));
+                }
+                *fail_after -= 1;
+            }
+            // only accept a few bytes at a time, to exercise short writes
+            let len = buf.len().min(5);
+            this.data.extend_from_slice(&buf[..len]);
+            std::task::Poll::Ready(Ok(len))
+        }
+
+        fn poll_flush(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            std::pin::Pin::into_inner(self).closed = true;
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    fn frames(data: &[u8]) -> Vec<Vec<u8>> {
+        let mut parser = crate::Parser::new();
+        parser.add_bytes(data);
+        std::iter::from_fn(|| parser.next_frame())
+            .map(|frame| frame.data)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_basic() {
+        let mut writer = Writer::with_capacity(100, Output::default());
+        writer
+            .frame_after(std::time::Duration::ZERO, b"foo")
+            .await
+            .unwrap();
+        writer
+            .frame_after(std::time::Duration::from_secs(1), b"bar")
+            .await
+            .unwrap();
+        assert!(writer.get_ref().data.is_empty());
+
+        // a failed write keeps whatever wasn't written yet
+        writer.get_mut().fail_after = Some(2);
+        assert!(matches!(
+            writer.flush().await,
+            Err(crate::Error::Write { .. })
+        ));
+        writer.get_mut().fail_after = None;
+        writer.flush().await.unwrap();
+        assert_eq!(frames(&writer.get_ref().data), [b"foo", b"bar"]);
+        assert!(!writer.get_ref().closed);
+        writer.shutdown().await.unwrap();
+        assert!(writer.get_ref().closed);
+
+        let mut writer = Writer::new(Output::default());
+        writer.frame(b"foo").await.unwrap();
+        assert_eq!(frames(&writer.get_ref().data), [b"foo"]);
+        writer.frame(b"bar").await.unwrap();
+        let output = writer.into_inner().await.unwrap();
+        assert_eq!(frames(&output.data), [b"foo", b"bar"]);
+        assert!(!output.closed);
+    }
+
+    #[cfg(feature = "sink")]
+    #[tokio::test]
+    async fn test_sink() {
+        use futures_sink::Sink as _;
+
+        let mut writer = Writer::with_capacity(100, Output::default());
+        for data in [b"foo", b"bar"] {
+            std::future::poll_fn(|cx| {
+                std::pin::Pin::new(&mut writer).poll_ready(cx)
+            })
+            .await
+            .unwrap();
+            std::pin::Pin::new(&mut writer)
+                .start_send(crate::Frame {
+                    time: std::time::Duration::ZERO,
+                    data: data.to_vec(),
+                })
+                .unwrap();
+        }
+        assert!(writer.get_ref().data.is_empty());
+        std::future::poll_fn(|cx| {
+            std::pin::Pin::new(&mut writer).poll_close(cx)
+        })
+        .await
+        .unwrap();
+        assert_eq!(frames(&writer.get_ref().data), [b"foo", b"bar"]);
+        assert!(writer.get_ref().closed);
+    }
 }